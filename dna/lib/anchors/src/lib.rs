@@ -0,0 +1,226 @@
+extern crate hdk;
+extern crate serde;
+extern crate serde_derive;
+extern crate serde_json;
+
+use hdk::prelude::*;
+
+/** Link type from the root anchor to the type anchors, and from each type anchor to its text anchors */
+pub const ANCHOR_LINK_TYPE: &str = "anchor->anchor";
+
+const ROOT_ANCHOR_TYPE: &str = "anchors";
+
+/**
+ * Well known entry from which other entries can be linked, identified by its type and its text.
+ *
+ * Anchors form a tree: the root anchor links to one anchor per type (without text),
+ * and each type anchor links to all the anchors of that type
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone, PartialEq)]
+pub struct Anchor {
+    pub anchor_type: String,
+    pub anchor_text: Option<String>,
+}
+
+impl Anchor {
+    pub fn new(anchor_type: &str, anchor_text: &str) -> Anchor {
+        Anchor {
+            anchor_type: anchor_type.into(),
+            anchor_text: Some(anchor_text.into()),
+        }
+    }
+
+    fn root() -> Anchor {
+        Anchor {
+            anchor_type: ROOT_ANCHOR_TYPE.into(),
+            anchor_text: None,
+        }
+    }
+
+    fn type_anchor(anchor_type: &str) -> Anchor {
+        Anchor {
+            anchor_type: anchor_type.into(),
+            anchor_text: None,
+        }
+    }
+
+    fn is_root(&self) -> bool {
+        self == &Anchor::root()
+    }
+
+    pub fn address<V: AnchorValidator>(&self) -> ZomeApiResult<Address> {
+        hdk::entry_address(&self.entry::<V>())
+    }
+
+    fn entry<V: AnchorValidator>(&self) -> Entry {
+        Entry::App(V::ENTRY_TYPE.into(), self.clone().into())
+    }
+}
+
+/**
+ * Hook that lets each zome define its own anchor entry type, and restrict which anchors of its types can be created.
+ *
+ * Holochain routes each app entry type to the first zome that defines it,
+ * so every zome of the DNA must use a distinct entry type name
+ */
+pub trait AnchorValidator {
+    /** Name of the anchor entry type of the zome, to be used as base in the link definitions of other entries */
+    const ENTRY_TYPE: &'static str;
+
+    fn validate_anchor(anchor: &Anchor) -> Result<(), String>;
+}
+
+pub fn anchor_definition<V: AnchorValidator + 'static>() -> ValidatingEntryType {
+    entry!(
+        name: V::ENTRY_TYPE,
+        description: "Well known entry to link other entries from, organized by type",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: |validation_data: hdk::EntryValidationData<Anchor>| {
+            match validation_data {
+                EntryValidationData::Create { entry, .. } => {
+                    if entry.anchor_type.is_empty() {
+                        return Err(String::from("Anchors must have a type"));
+                    }
+
+                    match entry.is_root() || entry.anchor_text.is_none() {
+                        true => Ok(()),
+                        false => V::validate_anchor(&entry)
+                    }
+                },
+                _ => Err(String::from("Cannot update or delete an anchor")),
+            }
+        },
+        links: [
+            to!(
+                V::ENTRY_TYPE,
+                link_type: ANCHOR_LINK_TYPE,
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, .. } => {
+                            let base: Anchor = hdk::utils::get_as_type(link.link.base().clone())?;
+                            let target: Anchor = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            let valid = match (base.is_root(), &base.anchor_text, &target.anchor_text) {
+                                (true, _, None) => !target.is_root(),
+                                (false, None, Some(_)) => base.anchor_type == target.anchor_type,
+                                _ => false,
+                            };
+
+                            match valid {
+                                true => Ok(()),
+                                false => Err(String::from("Anchors can only be linked from the root to a type anchor, or from a type anchor to an anchor of its type"))
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            )
+        ]
+    )
+}
+
+/** Handlers */
+
+/**
+ * Returns the address of the anchor with the given type and text, committing it and
+ * linking it from its type anchor.
+ *
+ * Anchors are committed on every call instead of being fetched first: committing an entry that
+ * already exists yields the same address, and the duplicated links are merged when listing anchors
+ */
+pub fn anchor<V: AnchorValidator>(anchor_type: &str, anchor_text: &str) -> ZomeApiResult<Address> {
+    let root_address = hdk::commit_entry(&Anchor::root().entry::<V>())?;
+    let type_anchor_address = hdk::commit_entry(&Anchor::type_anchor(anchor_type).entry::<V>())?;
+    let anchor_address = hdk::commit_entry(&Anchor::new(anchor_type, anchor_text).entry::<V>())?;
+
+    hdk::link_entries(
+        &root_address,
+        &type_anchor_address,
+        ANCHOR_LINK_TYPE,
+        anchor_type,
+    )?;
+    hdk::link_entries(
+        &type_anchor_address,
+        &anchor_address,
+        ANCHOR_LINK_TYPE,
+        anchor_text,
+    )?;
+
+    Ok(anchor_address)
+}
+
+/**
+ * Returns the address of the anchor with the given type and text without committing it,
+ * to read the links of anchors that may not exist yet
+ */
+pub fn anchor_address<V: AnchorValidator>(
+    anchor_type: &str,
+    anchor_text: &str,
+) -> ZomeApiResult<Address> {
+    Anchor::new(anchor_type, anchor_text).address::<V>()
+}
+
+/**
+ * Returns the addresses of all the anchors of the given type
+ */
+pub fn list_anchors<V: AnchorValidator>(anchor_type: &str) -> ZomeApiResult<Vec<Address>> {
+    list_children::<V>(&Anchor::type_anchor(anchor_type))
+}
+
+/**
+ * Returns all the types that have at least one anchor
+ */
+pub fn list_anchor_types<V: AnchorValidator>() -> ZomeApiResult<Vec<String>> {
+    let type_anchors = list_children::<V>(&Anchor::root())?;
+
+    type_anchors
+        .into_iter()
+        .map(|address| {
+            let anchor: Anchor = hdk::utils::get_as_type(address)?;
+            Ok(anchor.anchor_type)
+        })
+        .collect()
+}
+
+/** Validation helpers */
+
+/**
+ * Loads the anchor at the given address, failing if it is not of the given type.
+ * To be used in the validation of the links that other entries define from anchors
+ */
+pub fn get_anchor_of_type(address: &Address, anchor_type: &str) -> ZomeApiResult<Anchor> {
+    let anchor: Anchor = hdk::utils::get_as_type(address.clone())?;
+
+    match anchor.anchor_type == anchor_type && anchor.anchor_text.is_some() {
+        true => Ok(anchor),
+        false => Err(ZomeApiError::from(format!(
+            "Expected an anchor of type {}",
+            anchor_type
+        ))),
+    }
+}
+
+/** Helpers */
+
+fn list_children<V: AnchorValidator>(anchor: &Anchor) -> ZomeApiResult<Vec<Address>> {
+    let links = hdk::get_links(
+        &anchor.address::<V>()?,
+        LinkMatch::Exactly(ANCHOR_LINK_TYPE),
+        LinkMatch::Any,
+    )?;
+
+    let mut children: Vec<Address> = vec![];
+    for address in links.addresses() {
+        if !children.contains(&address) {
+            children.push(address);
+        }
+    }
+
+    Ok(children)
+}
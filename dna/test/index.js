@@ -1,3 +1,4 @@
+const crypto = require("crypto");
 const path = require("path");
 const tape = require("tape");
 
@@ -67,7 +68,8 @@ orchestrator.registerScenario(
     const badgeClass = JSON.parse(result.Ok.App[1]);
     t.deepEqual(badgeClass, {
       ...testBadgeClass,
-      creator_address: aliceAddress
+      creator_address: aliceAddress,
+      supersedes: null
     });
 
     await s.consistency();
@@ -85,7 +87,17 @@ orchestrator.registerScenario(
         description: "Test description",
         image: "Test image",
         creator_address: aliceAddress,
-        validators: 2
+        validators: 2,
+        min_lineages: null,
+        supersedes: null,
+        issuance_policy: "holders",
+        max_holders: null,
+        issuance_opens_at: null,
+        issuance_closes_at: null,
+        kind: { type: "claims" },
+        criteria: [],
+        alignments: [],
+        tags: []
       }
     ]);
 
@@ -115,7 +127,8 @@ orchestrator.registerScenario(
       }
     );
 
-    let badgesPromises = await getEntries(result.Ok)(alice);
+    t.equal(result.Ok.remaining_capacity, null);
+    let badgesPromises = await getEntries(result.Ok.badges)(alice);
     t.deepEqual(badges, [
       {
         recipient: bobAddress,
@@ -178,7 +191,7 @@ orchestrator.registerScenario(
       }
     );
 
-    badgeClaims = result.Ok.map(b => JSON.parse(b.Ok.App[1]));
+    badgeClaims = result.Ok.map(b => b.badge);
     t.deepEqual(badgeClaims, [
       {
         recipient: bobAddress,
@@ -270,4 +283,2419 @@ orchestrator.registerScenario(
   }
 );
 
+orchestrator.registerScenario(
+  "badge lineage and descendants",
+  async (s, t) => {
+    const { alice, bob, carol, dave, eve } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig,
+        carol: mainConfig,
+        dave: mainConfig,
+        eve: mainConfig
+      },
+      true
+    );
+
+    const aliceAddress = alice.instance("badges_instance").agentAddress;
+    const bobAddress = bob.instance("badges_instance").agentAddress;
+    const carolAddress = carol.instance("badges_instance").agentAddress;
+    const daveAddress = dave.instance("badges_instance").agentAddress;
+    const eveAddress = eve.instance("badges_instance").agentAddress;
+
+    const { Ok: badgeClassAddress } = await createBadgeClass()(alice);
+    await s.consistency();
+
+    await claimAgentDeservesBadge(bobAddress, badgeClassAddress)(alice);
+    await claimAgentDeservesBadge(carolAddress, badgeClassAddress)(alice);
+    await s.consistency();
+
+    await claimAgentDeservesBadge(daveAddress, badgeClassAddress)(bob);
+    await s.consistency();
+    await claimAgentDeservesBadge(daveAddress, badgeClassAddress)(carol);
+    await s.consistency();
+
+    let result = await dave.call(
+      "badges_instance",
+      "badges",
+      "get_badge_lineage",
+      {
+        recipient: daveAddress,
+        badge_class: badgeClassAddress
+      }
+    );
+
+    const lineage = result.Ok;
+    t.equal(lineage.agent, daveAddress);
+    t.deepEqual(
+      lineage.issuers.map(issuer => issuer.agent),
+      [bobAddress, carolAddress]
+    );
+    t.equal(lineage.issuers[0].depth, 1);
+    t.equal(lineage.issuers[0].issuers[0].agent, aliceAddress);
+    t.ok(lineage.issuers[0].issuers[0].is_creator);
+
+    result = await dave.call(
+      "badges_instance",
+      "badges",
+      "get_badge_descendants",
+      {
+        agent_address: aliceAddress,
+        badge_class: badgeClassAddress
+      }
+    );
+
+    const descendants = result.Ok.map(descendant => descendant.agent);
+    t.equal(descendants.length, 3);
+    t.ok(descendants.includes(daveAddress));
+    t.equal(result.Ok.find(d => d.agent === daveAddress).depth, 2);
+
+    // Badges claimed under a new version of the class extend the same tree
+    const { Ok: secondVersion } = await alice.call(
+      "badges_instance",
+      "badges",
+      "create_badge_class_version",
+      {
+        ...testBadgeClass,
+        validators: 1,
+        previous_version: badgeClassAddress
+      }
+    );
+    t.ok(secondVersion);
+    await s.consistency();
+
+    result = await claimAgentDeservesBadge(eveAddress, secondVersion)(bob);
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await dave.call(
+      "badges_instance",
+      "badges",
+      "get_badge_lineage",
+      {
+        recipient: eveAddress,
+        badge_class: badgeClassAddress
+      }
+    );
+    t.equal(result.Ok.issuers[0].agent, bobAddress);
+    t.equal(result.Ok.issuers[0].issuers[0].agent, aliceAddress);
+    t.ok(result.Ok.issuers[0].issuers[0].issued_at);
+
+    result = await dave.call(
+      "badges_instance",
+      "badges",
+      "get_badge_descendants",
+      {
+        agent_address: aliceAddress,
+        badge_class: secondVersion
+      }
+    );
+    t.equal(result.Ok.length, 4);
+    t.equal(result.Ok.find(d => d.agent === eveAddress).depth, 2);
+  }
+);
+
+orchestrator.registerScenario(
+  "badge class versions keep earlier badges valid",
+  async (s, t) => {
+    const { alice, bob, carol } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig,
+        carol: mainConfig
+      },
+      true
+    );
+
+    const bobAddress = bob.instance("badges_instance").agentAddress;
+    const carolAddress = carol.instance("badges_instance").agentAddress;
+
+    const { Ok: firstVersion } = await createBadgeClass({
+      ...testBadgeClass,
+      validators: 1
+    })(alice);
+    await s.consistency();
+
+    let result = await claimAgentDeservesBadge(bobAddress, firstVersion)(alice);
+    t.ok(result.Ok);
+    await s.consistency();
+
+    // Only the creator can create a new version
+    result = await bob.call(
+      "badges_instance",
+      "badges",
+      "create_badge_class_version",
+      {
+        ...testBadgeClass,
+        previous_version: firstVersion
+      }
+    );
+    t.notOk(result.Ok);
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "create_badge_class_version",
+      {
+        ...testBadgeClass,
+        description: "Fixed description",
+        validators: 1,
+        previous_version: firstVersion
+      }
+    );
+    const secondVersion = result.Ok;
+    t.ok(secondVersion);
+    await s.consistency();
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "get_all_badge_classes",
+      {}
+    );
+    t.deepEqual(result.Ok, [secondVersion]);
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "get_badge_class_versions",
+      { badge_class: firstVersion }
+    );
+    t.deepEqual(result.Ok, [firstVersion, secondVersion]);
+
+    // Bob earned the badge under the first version, and can issue the second one
+    result = await claimAgentDeservesBadge(carolAddress, secondVersion)(bob);
+    t.ok(result.Ok);
+    await s.consistency();
+
+    // Claims against a superseded version are made against the latest one
+    result = await claimAgentDeservesBadge(carolAddress, firstVersion)(alice);
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await alice.call("badges_instance", "badges", "get_badge", {
+      recipient: carolAddress,
+      badge_class: firstVersion
+    });
+    t.equal(result.Ok.badge.badge_class, secondVersion);
+    t.equal(result.Ok.badge.issuers.length, 2);
+
+    // A version can only be superseded once
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "create_badge_class_version",
+      {
+        ...testBadgeClass,
+        previous_version: firstVersion
+      }
+    );
+    t.notOk(result.Ok);
+
+    result = await alice.call("badges_instance", "badges", "get_badge", {
+      recipient: bobAddress,
+      badge_class: secondVersion
+    });
+    t.equal(result.Ok.badge.badge_class, firstVersion);
+  }
+);
+
+orchestrator.registerScenario(
+  "badge class administrators and ownership transfer",
+  async (s, t) => {
+    const { alice, bob, carol } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig,
+        carol: mainConfig
+      },
+      true
+    );
+
+    const aliceAddress = alice.instance("badges_instance").agentAddress;
+    const bobAddress = bob.instance("badges_instance").agentAddress;
+    const carolAddress = carol.instance("badges_instance").agentAddress;
+
+    const { Ok: badgeClassAddress } = await createBadgeClass()(alice);
+    await s.consistency();
+
+    // Bob is not an administrator yet, so he cannot issue the badge
+    let result = await claimAgentDeservesBadge(
+      carolAddress,
+      badgeClassAddress
+    )(bob);
+    t.notOk(result.Ok);
+
+    result = await alice.call("badges_instance", "badges", "add_class_admin", {
+      badge_class: badgeClassAddress,
+      admin: bobAddress
+    });
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await claimAgentDeservesBadge(carolAddress, badgeClassAddress)(bob);
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "transfer_class_ownership",
+      {
+        badge_class: badgeClassAddress,
+        new_owner: bobAddress
+      }
+    );
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await carol.call("badges_instance", "badges", "get_class_admins", {
+      badge_class: badgeClassAddress
+    });
+    t.deepEqual(result.Ok, {
+      badge_class: badgeClassAddress,
+      owner: bobAddress,
+      admins: []
+    });
+
+    // The class moves to the classes created by its new owner
+    result = await carol.call("badges_instance", "badges", "get_created_badges", {
+      agent_address: bobAddress
+    });
+    t.deepEqual(result.Ok, [badgeClassAddress]);
+
+    result = await carol.call("badges_instance", "badges", "get_created_badges", {
+      agent_address: aliceAddress
+    });
+    t.deepEqual(result.Ok, []);
+
+    result = await bob.call("badges_instance", "badges", "add_class_admin", {
+      badge_class: badgeClassAddress,
+      admin: carolAddress
+    });
+    t.ok(result.Ok);
+    await s.consistency();
+
+    // New versions keep the administrators of the class
+    result = await bob.call(
+      "badges_instance",
+      "badges",
+      "create_badge_class_version",
+      {
+        ...testBadgeClass,
+        previous_version: badgeClassAddress
+      }
+    );
+    const secondVersion = result.Ok;
+    t.ok(secondVersion);
+    await s.consistency();
+
+    result = await carol.call("badges_instance", "badges", "get_class_admins", {
+      badge_class: secondVersion
+    });
+    t.deepEqual(result.Ok, {
+      badge_class: secondVersion,
+      owner: bobAddress,
+      admins: [carolAddress]
+    });
+
+    // Alice is no longer the owner
+    result = await alice.call("badges_instance", "badges", "add_class_admin", {
+      badge_class: badgeClassAddress,
+      admin: aliceAddress
+    });
+    t.notOk(result.Ok);
+  }
+);
+
+orchestrator.registerScenario(
+  "allowlist and creator-only issuance policies",
+  async (s, t) => {
+    const { alice, bob, carol, dave } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig,
+        carol: mainConfig,
+        dave: mainConfig
+      },
+      true
+    );
+
+    const bobAddress = bob.instance("badges_instance").agentAddress;
+    const carolAddress = carol.instance("badges_instance").agentAddress;
+    const daveAddress = dave.instance("badges_instance").agentAddress;
+
+    const { Ok: allowlistClass } = await createBadgeClass({
+      ...testBadgeClass,
+      validators: 1,
+      issuance_policy: "allowlist"
+    })(alice);
+    const { Ok: creatorOnlyClass } = await createBadgeClass({
+      ...testBadgeClass,
+      name: "Creator only badge",
+      validators: 1,
+      issuance_policy: "creator-only"
+    })(alice);
+    await s.consistency();
+
+    // Holding the badge is not enough to issue it
+    await claimAgentDeservesBadge(bobAddress, allowlistClass)(alice);
+    await claimAgentDeservesBadge(bobAddress, creatorOnlyClass)(alice);
+    await s.consistency();
+
+    let result = await claimAgentDeservesBadge(carolAddress, allowlistClass)(bob);
+    t.notOk(result.Ok);
+    result = await claimAgentDeservesBadge(carolAddress, creatorOnlyClass)(bob);
+    t.notOk(result.Ok);
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "add_allowed_issuer",
+      {
+        badge_class: allowlistClass,
+        issuer: bobAddress
+      }
+    );
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await carol.call(
+      "badges_instance",
+      "badges",
+      "get_allowed_issuers",
+      { badge_class: allowlistClass }
+    );
+    t.deepEqual(result.Ok, [bobAddress]);
+
+    result = await claimAgentDeservesBadge(carolAddress, allowlistClass)(bob);
+    t.ok(result.Ok);
+    await s.consistency();
+
+    // New versions keep the allowlist of the version they supersede
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "create_badge_class_version",
+      {
+        ...testBadgeClass,
+        validators: 2,
+        min_lineages: 2,
+        issuance_policy: "allowlist",
+        previous_version: allowlistClass
+      }
+    );
+    const secondVersion = result.Ok;
+    t.ok(secondVersion);
+    await s.consistency();
+
+    result = await carol.call(
+      "badges_instance",
+      "badges",
+      "get_allowed_issuers",
+      { badge_class: secondVersion }
+    );
+    t.deepEqual(result.Ok, [bobAddress]);
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "add_allowed_issuer",
+      {
+        badge_class: secondVersion,
+        issuer: daveAddress
+      }
+    );
+    t.ok(result.Ok);
+    await s.consistency();
+
+    // Allowed issuers are lineages of their own, even without holding the badge
+    result = await claimAgentDeservesBadge(carolAddress, secondVersion)(bob);
+    t.ok(result.Ok);
+    result = await claimAgentDeservesBadge(carolAddress, secondVersion)(dave);
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await carol.call(
+      "badges_instance",
+      "badges",
+      "get_badges_to_recipient",
+      { agent_address: carolAddress }
+    );
+    t.equal(result.Ok.length, 2);
+  }
+);
+
+orchestrator.registerScenario(
+  "limited-edition and time-windowed badge classes",
+  async (s, t) => {
+    const { alice, bob, carol } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig,
+        carol: mainConfig
+      },
+      true
+    );
+
+    const bobAddress = bob.instance("badges_instance").agentAddress;
+    const carolAddress = carol.instance("badges_instance").agentAddress;
+
+    const { Ok: limitedClass } = await createBadgeClass({
+      ...testBadgeClass,
+      validators: 1,
+      max_holders: 1
+    })(alice);
+    const { Ok: closedClass } = await createBadgeClass({
+      ...testBadgeClass,
+      name: "Past event badge",
+      validators: 1,
+      issuance_opens_at: "2019-01-01T00:00:00+00:00",
+      issuance_closes_at: "2019-01-02T00:00:00+00:00"
+    })(alice);
+    await s.consistency();
+
+    let result = await claimAgentDeservesBadge(bobAddress, limitedClass)(alice);
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "get_badges_for_class",
+      { badge_class: limitedClass }
+    );
+    t.equal(result.Ok.remaining_capacity, 0);
+
+    // The only place of the badge is already taken
+    result = await claimAgentDeservesBadge(carolAddress, limitedClass)(alice);
+    t.notOk(result.Ok);
+
+    // Holders of earlier versions still count against the limit
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "create_badge_class_version",
+      {
+        ...testBadgeClass,
+        validators: 1,
+        max_holders: 1,
+        previous_version: limitedClass
+      }
+    );
+    const limitedSecondVersion = result.Ok;
+    t.ok(limitedSecondVersion);
+    await s.consistency();
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "get_badges_for_class",
+      { badge_class: limitedSecondVersion }
+    );
+    t.equal(result.Ok.remaining_capacity, 0);
+
+    result = await claimAgentDeservesBadge(carolAddress, limitedSecondVersion)(
+      alice
+    );
+    t.notOk(result.Ok);
+
+    // The capacity is the one of the latest version, whichever version is queried
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "create_badge_class_version",
+      {
+        ...testBadgeClass,
+        validators: 1,
+        max_holders: 2,
+        previous_version: limitedSecondVersion
+      }
+    );
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "get_badges_for_class",
+      { badge_class: limitedClass }
+    );
+    t.equal(result.Ok.remaining_capacity, 1);
+    t.equal(result.Ok.badges.length, 1);
+
+    result = await claimAgentDeservesBadge(carolAddress, limitedClass)(alice);
+    t.ok(result.Ok);
+
+    // The issuance window of the badge has already closed
+    result = await claimAgentDeservesBadge(bobAddress, closedClass)(alice);
+    t.notOk(result.Ok);
+  }
+);
+
+orchestrator.registerScenario(
+  "tiers of a family must be earned in order",
+  async (s, t) => {
+    const { alice, bob } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig
+      },
+      true
+    );
+
+    const bobAddress = bob.instance("badges_instance").agentAddress;
+
+    const { Ok: bronze } = await createBadgeClass({
+      ...testBadgeClass,
+      name: "Bronze",
+      validators: 1
+    })(alice);
+    const { Ok: silver } = await createBadgeClass({
+      ...testBadgeClass,
+      name: "Silver",
+      validators: 1
+    })(alice);
+    await s.consistency();
+
+    let result = await alice.call(
+      "badges_instance",
+      "badges",
+      "create_tier_family",
+      {
+        name: "Skill",
+        tiers: [bronze, silver]
+      }
+    );
+    const tierFamily = result.Ok;
+    t.ok(tierFamily);
+    await s.consistency();
+
+    // Bob does not hold the bronze tier yet
+    result = await claimAgentDeservesBadge(bobAddress, silver)(alice);
+    t.notOk(result.Ok);
+
+    result = await claimAgentDeservesBadge(bobAddress, bronze)(alice);
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await bob.call("badges_instance", "badges", "get_agent_tier", {
+      agent_address: bobAddress,
+      tier_family: tierFamily
+    });
+    t.equal(result.Ok.current_tier, 0);
+    t.equal(result.Ok.next_tier.badge_class, silver);
+    t.equal(result.Ok.next_tier.validators, 1);
+
+    result = await claimAgentDeservesBadge(bobAddress, silver)(alice);
+    t.ok(result.Ok);
+  }
+);
+
+orchestrator.registerScenario(
+  "new versions of tiers keep the order of their family",
+  async (s, t) => {
+    const { alice, bob } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig
+      },
+      true
+    );
+
+    const bobAddress = bob.instance("badges_instance").agentAddress;
+
+    const { Ok: bronze } = await createBadgeClass({
+      ...testBadgeClass,
+      name: "Bronze",
+      validators: 1
+    })(alice);
+    const { Ok: silver } = await createBadgeClass({
+      ...testBadgeClass,
+      name: "Silver",
+      validators: 1
+    })(alice);
+    await s.consistency();
+
+    let result = await alice.call(
+      "badges_instance",
+      "badges",
+      "create_tier_family",
+      {
+        name: "Skill",
+        tiers: [bronze, silver]
+      }
+    );
+    const tierFamily = result.Ok;
+    t.ok(tierFamily);
+    await s.consistency();
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "create_badge_class_version",
+      {
+        ...testBadgeClass,
+        name: "Silver",
+        description: "Fixed description",
+        validators: 1,
+        previous_version: silver
+      }
+    );
+    const newSilver = result.Ok;
+    t.ok(newSilver);
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "create_badge_class_version",
+      {
+        ...testBadgeClass,
+        name: "Bronze",
+        description: "Fixed description",
+        validators: 1,
+        previous_version: bronze
+      }
+    );
+    const newBronze = result.Ok;
+    t.ok(newBronze);
+    await s.consistency();
+
+    result = await alice.call("badges_instance", "badges", "get_tier_families", {
+      badge_class: newSilver
+    });
+    t.deepEqual(result.Ok, [tierFamily]);
+
+    // Bob does not hold any version of the bronze tier yet
+    result = await claimAgentDeservesBadge(bobAddress, newSilver)(alice);
+    t.notOk(result.Ok);
+
+    result = await claimAgentDeservesBadge(bobAddress, bronze)(alice);
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await bob.call("badges_instance", "badges", "get_agent_tier", {
+      agent_address: bobAddress,
+      tier_family: tierFamily
+    });
+    t.equal(result.Ok.current_tier, 0);
+    t.equal(result.Ok.next_tier.badge_class, newSilver);
+
+    result = await claimAgentDeservesBadge(bobAddress, silver)(alice);
+    t.ok(result.Ok);
+  }
+);
+
+orchestrator.registerScenario(
+  "pathway badges are earned by holding all their components",
+  async (s, t) => {
+    const { alice, bob } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig
+      },
+      true
+    );
+
+    const bobAddress = bob.instance("badges_instance").agentAddress;
+
+    const { Ok: componentA } = await createBadgeClass({
+      ...testBadgeClass,
+      name: "Component A",
+      validators: 1
+    })(alice);
+    const { Ok: componentB } = await createBadgeClass({
+      ...testBadgeClass,
+      name: "Component B",
+      validators: 1
+    })(alice);
+    await s.consistency();
+
+    const { Ok: capstone } = await createBadgeClass({
+      ...testBadgeClass,
+      name: "Capstone",
+      validators: 0,
+      kind: { type: "pathway", components: [componentA, componentB] }
+    })(alice);
+    await s.consistency();
+
+    await claimAgentDeservesBadge(bobAddress, componentA)(alice);
+    await s.consistency();
+
+    let result = await bob.call(
+      "badges_instance",
+      "badges",
+      "get_pathway_eligibility",
+      { agent_address: bobAddress, badge_class: capstone }
+    );
+    t.notOk(result.Ok.eligible);
+    t.deepEqual(result.Ok.missing_components, [componentB]);
+
+    // Creating the components does not count as holding them
+    const aliceAddress = alice.instance("badges_instance").agentAddress;
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "get_pathway_eligibility",
+      { agent_address: aliceAddress, badge_class: capstone }
+    );
+    t.notOk(result.Ok.eligible);
+    t.deepEqual(result.Ok.missing_components, [componentA, componentB]);
+
+    result = await bob.call("badges_instance", "badges", "earn_pathway_badge", {
+      badge_class: capstone
+    });
+    t.notOk(result.Ok);
+
+    await claimAgentDeservesBadge(bobAddress, componentB)(alice);
+    await s.consistency();
+
+    result = await bob.call("badges_instance", "badges", "earn_pathway_badge", {
+      badge_class: capstone
+    });
+    const capstoneBadge = result.Ok;
+    t.ok(capstoneBadge);
+    await s.consistency();
+
+    // Pathway badges cannot be claimed by issuers
+    result = await claimAgentDeservesBadge(bobAddress, capstone)(alice);
+    t.notOk(result.Ok);
+
+    // Pathway badges have no attestations, but are still dated when exported
+    const { Ok: collectionAddress } = await bob.call(
+      "badges_instance",
+      "badges",
+      "create_collection",
+      {
+        name: "Capstone",
+        badges: [capstoneBadge]
+      }
+    );
+    t.ok(collectionAddress);
+    await s.consistency();
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "export_open_badges_collection",
+      { collection: collectionAddress }
+    );
+    t.ok(result.Ok.assertions[0].issuedOn);
+  }
+);
+
+orchestrator.registerScenario(
+  "agents can apply for badges and issuers endorse or reject them",
+  async (s, t) => {
+    const { alice, bob, carol } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig,
+        carol: mainConfig
+      },
+      true
+    );
+
+    const bobAddress = bob.instance("badges_instance").agentAddress;
+
+    const { Ok: badgeClassAddress } = await createBadgeClass({
+      ...testBadgeClass,
+      validators: 1
+    })(alice);
+    await s.consistency();
+
+    let result = await bob.call("badges_instance", "badges", "apply_for_badge", {
+      badge_class: badgeClassAddress,
+      evidences: [],
+      statement: "I deserve this badge"
+    });
+    const applicationAddress = result.Ok;
+    t.ok(applicationAddress);
+    await s.consistency();
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "get_pending_applications",
+      { badge_class: badgeClassAddress }
+    );
+    t.equal(result.Ok.length, 1);
+    t.equal(result.Ok[0].application.applicant, bobAddress);
+
+    // Applications remain pending in the new versions of the class
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "create_badge_class_version",
+      {
+        ...testBadgeClass,
+        description: "Fixed description",
+        validators: 1,
+        previous_version: badgeClassAddress
+      }
+    );
+    const secondVersion = result.Ok;
+    t.ok(secondVersion);
+    await s.consistency();
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "get_pending_applications",
+      { badge_class: secondVersion }
+    );
+    t.equal(result.Ok.length, 1);
+
+    // Carol is not a valid issuer of the badge
+    result = await carol.call(
+      "badges_instance",
+      "badges",
+      "reject_application",
+      { application: applicationAddress }
+    );
+    t.notOk(result.Ok);
+
+    result = await carol.call(
+      "badges_instance",
+      "badges",
+      "get_pending_applications",
+      { badge_class: badgeClassAddress }
+    );
+    t.notOk(result.Ok);
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "endorse_application",
+      {
+        application: applicationAddress,
+        statement: "Bob showed me his work",
+        confidence: "medium"
+      }
+    );
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await bob.call(
+      "badges_instance",
+      "badges",
+      "get_my_applications",
+      {}
+    );
+    t.equal(result.Ok[0].status, "completed");
+    t.equal(result.Ok[0].endorsed_by.length, 1);
+  }
+);
+
+orchestrator.registerScenario(
+  "claims carry the attestation of their issuer",
+  async (s, t) => {
+    const { alice, bob } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig
+      },
+      true
+    );
+
+    const aliceAddress = alice.instance("badges_instance").agentAddress;
+    const bobAddress = bob.instance("badges_instance").agentAddress;
+
+    const { Ok: badgeClassAddress } = await createBadgeClass()(alice);
+    await s.consistency();
+
+    let result = await claimAgentDeservesBadge(
+      bobAddress,
+      badgeClassAddress,
+      "Bob mentored the team",
+      "medium"
+    )(alice);
+    const badgeAddress = result.Ok;
+    t.ok(badgeAddress);
+    await s.consistency();
+
+    result = await bob.call("badges_instance", "badges", "get_badge", {
+      recipient: bobAddress,
+      badge_class: badgeClassAddress
+    });
+    t.deepEqual(result.Ok.badge.issuers, [aliceAddress]);
+    t.equal(result.Ok.address, badgeAddress);
+    t.equal(result.Ok.attestations.length, 1);
+
+    const { attestation, issued_at } = result.Ok.attestations[0];
+    t.deepEqual(attestation, {
+      badge: badgeAddress,
+      issuer: aliceAddress,
+      statement: "Bob mentored the team",
+      confidence: "medium",
+      criteria: [],
+      delegate: null
+    });
+    t.ok(issued_at);
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "get_badges_from_issuer",
+      { agent_address: aliceAddress }
+    );
+    t.equal(result.Ok[0].address, badgeAddress);
+    t.equal(
+      result.Ok[0].attestations[0].attestation.statement,
+      "Bob mentored the team"
+    );
+
+    result = await bob.call(
+      "badges_instance",
+      "badges",
+      "get_attestations_from_issuer",
+      { agent_address: aliceAddress }
+    );
+    t.equal(result.Ok[0].attestation.statement, "Bob mentored the team");
+  }
+);
+
+orchestrator.registerScenario(
+  "required criteria must be attested by as many issuers as validators",
+  async (s, t) => {
+    const { alice, bob, carol } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig,
+        carol: mainConfig
+      },
+      true
+    );
+
+    const bobAddress = bob.instance("badges_instance").agentAddress;
+    const carolAddress = carol.instance("badges_instance").agentAddress;
+
+    const { Ok: badgeClassAddress } = await createBadgeClass({
+      ...testBadgeClass,
+      validators: 1,
+      criteria: [
+        { id: "code", text: "Writes idiomatic code", required: true },
+        { id: "docs", text: "Documents their work", required: false }
+      ]
+    })(alice);
+    await s.consistency();
+
+    // Criteria must be defined in the class
+    let result = await claimAgentDeservesBadge(
+      bobAddress,
+      badgeClassAddress,
+      "Test statement",
+      "high",
+      ["tests"]
+    )(alice);
+    t.notOk(result.Ok);
+
+    result = await claimAgentDeservesBadge(
+      bobAddress,
+      badgeClassAddress,
+      "Test statement",
+      "high",
+      ["docs"]
+    )(alice);
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await bob.call(
+      "badges_instance",
+      "badges",
+      "get_criteria_coverage",
+      { recipient: bobAddress, badge_class: badgeClassAddress }
+    );
+    t.equal(result.Ok[0].satisfied, false);
+    t.equal(result.Ok[1].satisfied, true);
+
+    // Bob has not completed the required criterion, so he cannot issue yet
+    result = await claimAgentDeservesBadge(
+      carolAddress,
+      badgeClassAddress,
+      "Test statement",
+      "high",
+      ["code"]
+    )(bob);
+    t.notOk(result.Ok);
+  }
+);
+
+orchestrator.registerScenario(
+  "third parties can endorse badge classes and issuers",
+  async (s, t) => {
+    const { alice, bob } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig
+      },
+      true
+    );
+
+    const aliceAddress = alice.instance("badges_instance").agentAddress;
+    const bobAddress = bob.instance("badges_instance").agentAddress;
+
+    const { Ok: badgeClassAddress } = await createBadgeClass()(alice);
+    await s.consistency();
+
+    // Creators cannot endorse their own badge classes
+    let result = await alice.call("badges_instance", "badges", "endorse", {
+      target: badgeClassAddress,
+      target_type: "badge-class",
+      claim: "Great badge"
+    });
+    t.notOk(result.Ok);
+
+    result = await bob.call("badges_instance", "badges", "endorse", {
+      target: badgeClassAddress,
+      target_type: "badge-class",
+      claim: "This badge reflects real skills"
+    });
+    t.ok(result.Ok);
+
+    // Issuer endorsements must target an agent or an organization
+    result = await bob.call("badges_instance", "badges", "endorse", {
+      target: badgeClassAddress,
+      target_type: "issuer",
+      claim: "Great issuer"
+    });
+    t.notOk(result.Ok);
+
+    result = await bob.call("badges_instance", "badges", "endorse", {
+      target: aliceAddress,
+      target_type: "issuer",
+      claim: "Alice is a rigorous issuer"
+    });
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await alice.call("badges_instance", "badges", "get_endorsements", {
+      target: badgeClassAddress
+    });
+    t.deepEqual(result.Ok, [
+      {
+        target: badgeClassAddress,
+        target_type: "badge-class",
+        endorser: bobAddress,
+        claim: "This badge reflects real skills"
+      }
+    ]);
+
+    result = await alice.call("badges_instance", "badges", "get_endorsements", {
+      target: aliceAddress
+    });
+    t.equal(result.Ok[0].claim, "Alice is a rigorous issuer");
+
+    const { Ok: organizationAddress } = await alice.call(
+      "badges_instance",
+      "badges",
+      "create_organization",
+      { name: "University" }
+    );
+    await s.consistency();
+
+    // Members of an organization cannot endorse it
+    result = await alice.call("badges_instance", "badges", "endorse", {
+      target: organizationAddress,
+      target_type: "issuer",
+      claim: "Great university"
+    });
+    t.notOk(result.Ok);
+
+    result = await bob.call("badges_instance", "badges", "endorse", {
+      target: organizationAddress,
+      target_type: "issuer",
+      claim: "The university issues rigorously"
+    });
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await alice.call("badges_instance", "badges", "get_endorsements", {
+      target: organizationAddress
+    });
+    t.equal(result.Ok[0].claim, "The university issues rigorously");
+  }
+);
+
+orchestrator.registerScenario(
+  "badge classes can be aligned to external competency frameworks",
+  async (s, t) => {
+    const { alice } = await s.players({ alice: mainConfig }, true);
+
+    const alignment = {
+      target_name: "Programming",
+      target_url: "https://esco.ec.europa.eu/skill/programming",
+      target_description: null,
+      target_framework: "ESCO",
+      target_code: "S1.2"
+    };
+
+    // Alignments must have a target url
+    let result = await createBadgeClass({
+      ...testBadgeClass,
+      alignments: [{ ...alignment, target_url: "" }]
+    })(alice);
+    t.notOk(result.Ok);
+
+    const { Ok: badgeClassAddress } = await createBadgeClass({
+      ...testBadgeClass,
+      alignments: [alignment]
+    })(alice);
+    await createBadgeClass()(alice);
+    await s.consistency();
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "get_badge_classes_by_alignment",
+      { framework: "ESCO", code: "S1.2" }
+    );
+    t.deepEqual(result.Ok, [badgeClassAddress]);
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "get_badge_classes_by_alignment",
+      { framework: "ESCO", code: "S9.9" }
+    );
+    t.deepEqual(result.Ok, []);
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "export_open_badges_class",
+      { badge_class: badgeClassAddress }
+    );
+    t.equal(result.Ok["@context"], "https://w3id.org/openbadges/v2");
+    t.equal(result.Ok.type, "BadgeClass");
+    t.deepEqual(result.Ok.alignment, [
+      {
+        targetName: "Programming",
+        targetUrl: "https://esco.ec.europa.eu/skill/programming",
+        targetFramework: "ESCO",
+        targetCode: "S1.2"
+      }
+    ]);
+
+    // Classes whose latest version dropped the alignment are no longer aligned
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "create_badge_class_version",
+      {
+        ...testBadgeClass,
+        alignments: [],
+        previous_version: badgeClassAddress
+      }
+    );
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "get_badge_classes_by_alignment",
+      { framework: "ESCO", code: "S1.2" }
+    );
+    t.deepEqual(result.Ok, []);
+  }
+);
+
+orchestrator.registerScenario(
+  "badge classes can be browsed by tag",
+  async (s, t) => {
+    const { alice } = await s.players({ alice: mainConfig }, true);
+
+    const { Ok: rustClass } = await createBadgeClass({
+      ...testBadgeClass,
+      tags: ["Rust", "programming"]
+    })(alice);
+    const { Ok: gardeningClass } = await createBadgeClass({
+      ...testBadgeClass,
+      name: "Gardening",
+      tags: ["gardening"]
+    })(alice);
+    const { Ok: haskellClass } = await createBadgeClass({
+      ...testBadgeClass,
+      name: "Haskell",
+      tags: ["programming"]
+    })(alice);
+    await s.consistency();
+
+    let result = await alice.call("badges_instance", "badges", "get_tags", {});
+    t.deepEqual(result.Ok, [
+      { tag: "programming", count: 2 },
+      { tag: "gardening", count: 1 },
+      { tag: "rust", count: 1 }
+    ]);
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "get_badge_classes_by_tag",
+      { tag: "Programming" }
+    );
+    t.equal(result.Ok.length, 2);
+    t.ok(result.Ok.includes(rustClass));
+    t.ok(result.Ok.includes(haskellClass));
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "get_badge_classes_by_tag",
+      { tag: "gardening" }
+    );
+    t.deepEqual(result.Ok, [gardeningClass]);
+
+    // Classes whose latest version dropped a tag are no longer counted for it
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "create_badge_class_version",
+      {
+        ...testBadgeClass,
+        name: "Gardening",
+        tags: ["outdoors"],
+        previous_version: gardeningClass
+      }
+    );
+    const newGardeningClass = result.Ok;
+    t.ok(newGardeningClass);
+    await s.consistency();
+
+    result = await alice.call("badges_instance", "badges", "get_tags", {});
+    t.deepEqual(result.Ok, [
+      { tag: "programming", count: 2 },
+      { tag: "outdoors", count: 1 },
+      { tag: "rust", count: 1 }
+    ]);
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "get_badge_classes_by_tag",
+      { tag: "gardening" }
+    );
+    t.deepEqual(result.Ok, []);
+  }
+);
+
+orchestrator.registerScenario(
+  "badge classes can be searched by name and description",
+  async (s, t) => {
+    const { alice } = await s.players({ alice: mainConfig }, true);
+
+    const { Ok: rustClass } = await createBadgeClass({
+      ...testBadgeClass,
+      name: "Rust programming",
+      description: "Writes safe systems code"
+    })(alice);
+    const { Ok: haskellClass } = await createBadgeClass({
+      ...testBadgeClass,
+      name: "Haskell",
+      description: "Functional programming"
+    })(alice);
+    await createBadgeClass({
+      ...testBadgeClass,
+      name: "Gardening",
+      description: "Grows vegetables"
+    })(alice);
+    await s.consistency();
+
+    let result = await alice.call(
+      "badges_instance",
+      "badges",
+      "search_badge_classes",
+      { query: "Rust programming", limit: 10 }
+    );
+    t.deepEqual(result.Ok, [
+      { badge_class: rustClass, matched_terms: 2 },
+      { badge_class: haskellClass, matched_terms: 1 }
+    ]);
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "search_badge_classes",
+      { query: "programming", limit: 1 }
+    );
+    t.equal(result.Ok.length, 1);
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "search_badge_classes",
+      { query: "cooking", limit: 10 }
+    );
+    t.deepEqual(result.Ok, []);
+
+    // Terms removed in the latest version of a class no longer match it
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "create_badge_class_version",
+      {
+        ...testBadgeClass,
+        name: "Haskell",
+        description: "Pure functions",
+        previous_version: haskellClass
+      }
+    );
+    const newHaskellClass = result.Ok;
+    t.ok(newHaskellClass);
+    await s.consistency();
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "search_badge_classes",
+      { query: "programming", limit: 10 }
+    );
+    t.deepEqual(result.Ok, [{ badge_class: rustClass, matched_terms: 1 }]);
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "search_badge_classes",
+      { query: "functions", limit: 10 }
+    );
+    t.deepEqual(result.Ok, [{ badge_class: newHaskellClass, matched_terms: 1 }]);
+  }
+);
+
+orchestrator.registerScenario(
+  "vouched agents are listed from the members anchor",
+  async (s, t) => {
+    const { alice, bob } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig
+      },
+      true
+    );
+
+    const bobAddress = bob.instance("badges_instance").agentAddress;
+
+    let result = await alice.call(
+      "badges_instance",
+      "social-triangulation",
+      "vouch_for_agent",
+      { agent_address: bobAddress }
+    );
+    t.equal(result.Ok, null);
+    await s.consistency();
+
+    result = await bob.call(
+      "badges_instance",
+      "social-triangulation",
+      "get_vouched_agents",
+      {}
+    );
+    t.deepEqual(result.Ok, [bobAddress]);
+  }
+);
+
+orchestrator.registerScenario(
+  "agents have profiles with unique handles",
+  async (s, t) => {
+    const { alice, bob } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig
+      },
+      true
+    );
+
+    const aliceAddress = alice.instance("badges_instance").agentAddress;
+
+    let result = await alice.call("badges_instance", "profiles", "create_profile", {
+      handle: "alice_dev",
+      display_name: "Alice",
+      avatar: null,
+      bio: "Rust developer"
+    });
+    t.ok(result.Ok);
+    await s.consistency();
+
+    // Agents have only one profile
+    result = await alice.call("badges_instance", "profiles", "create_profile", {
+      handle: "alice_ops",
+      display_name: "Alice",
+      avatar: null,
+      bio: null
+    });
+    t.notOk(result.Ok);
+
+    // Bob cannot take the same handle
+    result = await bob.call("badges_instance", "profiles", "create_profile", {
+      handle: "Alice_Dev",
+      display_name: "Bob",
+      avatar: null,
+      bio: null
+    });
+    t.notOk(result.Ok);
+
+    result = await bob.call("badges_instance", "profiles", "create_profile", {
+      handle: "alibob",
+      display_name: "Bob",
+      avatar: null,
+      bio: null
+    });
+    t.ok(result.Ok);
+
+    result = await alice.call("badges_instance", "profiles", "update_profile", {
+      display_name: "Alice Liddell",
+      avatar: "https://example.com/alice.png",
+      bio: "Rust developer"
+    });
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await bob.call("badges_instance", "profiles", "get_profile", {
+      agent_address: aliceAddress
+    });
+    t.deepEqual(result.Ok, {
+      agent_address: aliceAddress,
+      handle: "alice_dev",
+      display_name: "Alice Liddell",
+      avatar: "https://example.com/alice.png",
+      bio: "Rust developer"
+    });
+
+    result = await bob.call("badges_instance", "profiles", "get_agent_by_handle", {
+      handle: "alice_dev"
+    });
+    t.equal(result.Ok, aliceAddress);
+
+    result = await bob.call("badges_instance", "profiles", "search_profiles", {
+      handle_prefix: "ali"
+    });
+    t.deepEqual(
+      result.Ok.map(profile => profile.handle),
+      ["alibob", "alice_dev"]
+    );
+
+    result = await bob.call("badges_instance", "profiles", "search_profiles", {
+      handle_prefix: "alic"
+    });
+    t.deepEqual(result.Ok.map(profile => profile.handle), ["alice_dev"]);
+  }
+);
+
+orchestrator.registerScenario(
+  "organizations issue badges through their members",
+  async (s, t) => {
+    const { alice, bob, carol, dave } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig,
+        carol: mainConfig,
+        dave: mainConfig
+      },
+      true
+    );
+
+    const bobAddress = bob.instance("badges_instance").agentAddress;
+    const carolAddress = carol.instance("badges_instance").agentAddress;
+    const daveAddress = dave.instance("badges_instance").agentAddress;
+
+    const { Ok: organizationAddress } = await alice.call(
+      "badges_instance",
+      "badges",
+      "create_organization",
+      { name: "University" }
+    );
+    t.ok(organizationAddress);
+
+    let result = await alice.call(
+      "badges_instance",
+      "badges",
+      "set_organization_member",
+      {
+        organization: organizationAddress,
+        agent_address: bobAddress,
+        role: "issuer"
+      }
+    );
+    t.ok(result.Ok);
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "set_organization_member",
+      {
+        organization: organizationAddress,
+        agent_address: carolAddress,
+        role: "member"
+      }
+    );
+    t.ok(result.Ok);
+    await s.consistency();
+
+    // Only admins can create badge classes on behalf of the organization
+    result = await createBadgeClass({
+      ...testBadgeClass,
+      issuance_policy: "creator-only",
+      organization: organizationAddress
+    })(bob);
+    t.notOk(result.Ok);
+
+    const { Ok: badgeClassAddress } = await createBadgeClass({
+      ...testBadgeClass,
+      issuance_policy: "creator-only",
+      organization: organizationAddress
+    })(alice);
+    await s.consistency();
+
+    result = await alice.call("badges_instance", "badges", "get_created_badges", {
+      agent_address: organizationAddress
+    });
+    t.deepEqual(result.Ok, [badgeClassAddress]);
+
+    // Members without the issuer role cannot issue on behalf of the organization
+    result = await claimAgentDeservesBadge(daveAddress, badgeClassAddress)(carol);
+    t.notOk(result.Ok);
+
+    // Issuers complete the badge on their own, as the organization
+    result = await claimAgentDeservesBadge(daveAddress, badgeClassAddress)(bob);
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await dave.call("badges_instance", "badges", "get_badge", {
+      badge_class: badgeClassAddress,
+      recipient: daveAddress
+    });
+    t.deepEqual(result.Ok.badge.issuers, [bobAddress]);
+
+    result = await dave.call("badges_instance", "badges", "get_badges_to_recipient", {
+      agent_address: daveAddress
+    });
+    t.equal(result.Ok.length, 1);
+  }
+);
+
+orchestrator.registerScenario(
+  "issuers can delegate claims to automated agents",
+  async (s, t) => {
+    const { alice, bot, bob, carol } = await s.players(
+      {
+        alice: mainConfig,
+        bot: mainConfig,
+        bob: mainConfig,
+        carol: mainConfig
+      },
+      true
+    );
+
+    const aliceAddress = alice.instance("badges_instance").agentAddress;
+    const botAddress = bot.instance("badges_instance").agentAddress;
+    const bobAddress = bob.instance("badges_instance").agentAddress;
+    const carolAddress = carol.instance("badges_instance").agentAddress;
+
+    const { Ok: badgeClassAddress } = await createBadgeClass({
+      ...testBadgeClass,
+      validators: 1
+    })(alice);
+    const { Ok: otherClassAddress } = await createBadgeClass()(alice);
+    await s.consistency();
+
+    // Delegations must expire after they are granted
+    let result = await alice.call("badges_instance", "badges", "delegate_issuing", {
+      delegate: botAddress,
+      badge_classes: [badgeClassAddress],
+      expires_at: "2000-01-01T00:00:00+00:00"
+    });
+    t.notOk(result.Ok);
+
+    result = await alice.call("badges_instance", "badges", "delegate_issuing", {
+      delegate: botAddress,
+      badge_classes: [badgeClassAddress],
+      expires_at: "2100-01-01T00:00:00+00:00"
+    });
+    const delegationAddress = result.Ok;
+    t.ok(delegationAddress);
+    await s.consistency();
+
+    // The delegation is scoped to the given badge classes
+    result = await bot.call(
+      "badges_instance",
+      "badges",
+      "claim_agent_deserves_badge",
+      {
+        recipient: bobAddress,
+        badge_class: otherClassAddress,
+        evidences: [],
+        statement: "Passed certification",
+        confidence: "high",
+        on_behalf_of: aliceAddress
+      }
+    );
+    t.notOk(result.Ok);
+
+    result = await bot.call(
+      "badges_instance",
+      "badges",
+      "claim_agent_deserves_badge",
+      {
+        recipient: bobAddress,
+        badge_class: badgeClassAddress,
+        evidences: [],
+        statement: "Passed certification",
+        confidence: "high",
+        on_behalf_of: aliceAddress
+      }
+    );
+    const badgeAddress = result.Ok;
+    t.ok(badgeAddress);
+    await s.consistency();
+
+    result = await bob.call("badges_instance", "badges", "get_badge", {
+      recipient: bobAddress,
+      badge_class: badgeClassAddress
+    });
+    t.deepEqual(result.Ok.badge.issuers, [aliceAddress]);
+    t.equal(result.Ok.attestations[0].attestation.issuer, aliceAddress);
+    t.equal(result.Ok.attestations[0].attestation.delegate, botAddress);
+
+    // Only the delegator can revoke the delegation
+    result = await bot.call("badges_instance", "badges", "revoke_delegation", {
+      delegation_address: delegationAddress
+    });
+    t.notOk(result.Ok);
+
+    result = await alice.call("badges_instance", "badges", "revoke_delegation", {
+      delegation_address: delegationAddress
+    });
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await bot.call(
+      "badges_instance",
+      "badges",
+      "claim_agent_deserves_badge",
+      {
+        recipient: carolAddress,
+        badge_class: badgeClassAddress,
+        evidences: [],
+        statement: "Passed certification",
+        confidence: "high",
+        on_behalf_of: aliceAddress
+      }
+    );
+    t.notOk(result.Ok);
+
+    // The badges claimed before the revocation are still valid
+    result = await bob.call("badges_instance", "badges", "get_badge", {
+      recipient: bobAddress,
+      badge_class: badgeClassAddress
+    });
+    t.deepEqual(result.Ok.badge.issuers, [aliceAddress]);
+  }
+);
+
+orchestrator.registerScenario(
+  "recipients attach private evidences and disclose them selectively",
+  async (s, t) => {
+    const { alice, bob, carol } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig,
+        carol: mainConfig
+      },
+      true
+    );
+
+    const bobAddress = bob.instance("badges_instance").agentAddress;
+    const carolAddress = carol.instance("badges_instance").agentAddress;
+
+    const { Ok: badgeClassAddress } = await createBadgeClass({
+      ...testBadgeClass,
+      validators: 1
+    })(alice);
+    await s.consistency();
+
+    // Salts must be long enough to hide the content
+    let result = await bob.call(
+      "badges_instance",
+      "badges",
+      "attach_private_evidence",
+      { badge_class: badgeClassAddress, content: "Medical report", salt: "salt" }
+    );
+    t.notOk(result.Ok);
+
+    // Evidences can be attached before the first claim
+    const { Ok: commitmentAddress } = await bob.call(
+      "badges_instance",
+      "badges",
+      "attach_private_evidence",
+      {
+        badge_class: badgeClassAddress,
+        content: "Medical report",
+        salt: crypto.randomBytes(32).toString("hex")
+      }
+    );
+    t.ok(commitmentAddress);
+    await s.consistency();
+
+    // The owner of the class can check the evidence before claiming
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "decrypt_private_evidence",
+      { commitment_address: commitmentAddress }
+    );
+    t.equal(result.Ok, "Medical report");
+
+    const { Ok: badgeAddress } = await claimAgentDeservesBadge(
+      bobAddress,
+      badgeClassAddress
+    )(alice);
+    await s.consistency();
+
+    result = await carol.call(
+      "badges_instance",
+      "badges",
+      "get_evidence_commitments",
+      { badge_address: badgeAddress }
+    );
+    t.deepEqual(result.Ok, [commitmentAddress]);
+
+    // Only the recipient and the issuers of the class hold the content
+    result = await carol.call(
+      "badges_instance",
+      "badges",
+      "decrypt_private_evidence",
+      { commitment_address: commitmentAddress }
+    );
+    t.notOk(result.Ok);
+
+    result = await carol.call(
+      "badges_instance",
+      "badges",
+      "request_private_evidence",
+      { commitment_address: commitmentAddress }
+    );
+    t.notOk(result.Ok);
+
+    result = await bob.call(
+      "badges_instance",
+      "badges",
+      "decrypt_private_evidence",
+      { commitment_address: commitmentAddress }
+    );
+    t.equal(result.Ok, "Medical report");
+
+    // Agents that become issuers later request the evidence from the recipient
+    await claimAgentDeservesBadge(carolAddress, badgeClassAddress)(alice);
+    await s.consistency();
+
+    result = await carol.call(
+      "badges_instance",
+      "badges",
+      "request_private_evidence",
+      { commitment_address: commitmentAddress }
+    );
+    t.equal(result.Ok, null);
+
+    result = await carol.call(
+      "badges_instance",
+      "badges",
+      "decrypt_private_evidence",
+      { commitment_address: commitmentAddress }
+    );
+    t.equal(result.Ok, "Medical report");
+
+    const { Ok: opening } = await bob.call(
+      "badges_instance",
+      "badges",
+      "disclose_private_evidence",
+      { commitment_address: commitmentAddress }
+    );
+
+    result = await carol.call(
+      "badges_instance",
+      "badges",
+      "verify_evidence_disclosure",
+      { commitment_address: commitmentAddress, opening }
+    );
+    t.equal(result.Ok, true);
+
+    result = await carol.call(
+      "badges_instance",
+      "badges",
+      "verify_evidence_disclosure",
+      {
+        commitment_address: commitmentAddress,
+        opening: { ...opening, content: "Forged report" }
+      }
+    );
+    t.equal(result.Ok, false);
+  }
+);
+
+orchestrator.registerScenario(
+  "recipients choose who can see their badges",
+  async (s, t) => {
+    const { alice, bob, carol, dave } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig,
+        carol: mainConfig,
+        dave: mainConfig
+      },
+      true
+    );
+
+    const aliceAddress = alice.instance("badges_instance").agentAddress;
+    const bobAddress = bob.instance("badges_instance").agentAddress;
+    const carolAddress = carol.instance("badges_instance").agentAddress;
+
+    const { Ok: badgeClassAddress } = await createBadgeClass({
+      ...testBadgeClass,
+      validators: 1
+    })(alice);
+    await s.consistency();
+
+    const { Ok: badgeAddress } = await claimAgentDeservesBadge(
+      bobAddress,
+      badgeClassAddress
+    )(alice);
+    await s.consistency();
+
+    let result = await dave.call(
+      "badges_instance",
+      "badges",
+      "get_badges_to_recipient",
+      { agent_address: bobAddress }
+    );
+    t.deepEqual(result.Ok, [badgeAddress]);
+
+    // Only the recipient can change the visibility of a badge
+    result = await alice.call("badges_instance", "badges", "set_badge_visibility", {
+      badge_address: badgeAddress,
+      visibility: { type: "hidden" }
+    });
+    t.notOk(result.Ok);
+
+    result = await bob.call("badges_instance", "badges", "set_badge_visibility", {
+      badge_address: badgeAddress,
+      visibility: { type: "shared", agents: [carolAddress] }
+    });
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await dave.call(
+      "badges_instance",
+      "badges",
+      "get_badges_to_recipient",
+      { agent_address: bobAddress }
+    );
+    t.deepEqual(result.Ok, []);
+
+    // The agents a badge is shared with are not published
+    result = await dave.call("badges_instance", "badges", "get_badge_visibility", {
+      badge_address: badgeAddress
+    });
+    t.equal(result.Ok.type, "shared");
+    t.notOk(JSON.stringify(result.Ok).includes(carolAddress));
+
+    result = await carol.call(
+      "badges_instance",
+      "badges",
+      "get_badges_to_recipient",
+      { agent_address: bobAddress }
+    );
+    t.deepEqual(result.Ok, [badgeAddress]);
+
+    result = await bob.call("badges_instance", "badges", "set_badge_visibility", {
+      badge_address: badgeAddress,
+      visibility: { type: "hidden" }
+    });
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await carol.call(
+      "badges_instance",
+      "badges",
+      "get_badges_to_recipient",
+      { agent_address: bobAddress }
+    );
+    t.deepEqual(result.Ok, []);
+
+    // Hidden badges are left out of every listing
+    result = await carol.call("badges_instance", "badges", "get_badges_from_issuer", {
+      agent_address: aliceAddress
+    });
+    t.deepEqual(result.Ok, []);
+
+    result = await carol.call("badges_instance", "badges", "get_badges_for_class", {
+      badge_class: badgeClassAddress
+    });
+    t.deepEqual(result.Ok.badges, []);
+
+    // Except for the issuers of the badge and the admins of its class
+    result = await alice.call("badges_instance", "badges", "get_badges_from_issuer", {
+      agent_address: aliceAddress
+    });
+    t.deepEqual(result.Ok.map(b => b.address), [badgeAddress]);
+
+    result = await alice.call("badges_instance", "badges", "get_badges_for_class", {
+      badge_class: badgeClassAddress
+    });
+    t.deepEqual(result.Ok.badges, [badgeAddress]);
+
+    result = await bob.call(
+      "badges_instance",
+      "badges",
+      "get_badges_to_recipient",
+      { agent_address: bobAddress }
+    );
+    t.deepEqual(result.Ok, [badgeAddress]);
+
+    // Hidden badges can still be verified by their address
+    result = await dave.call("badges_instance", "badges", "get_badge", {
+      badge_class: badgeClassAddress,
+      recipient: bobAddress
+    });
+    t.equal(result.Ok.badge.recipient, bobAddress);
+  }
+);
+
+orchestrator.registerScenario(
+  "recipients curate collections of their completed badges",
+  async (s, t) => {
+    const { alice, bob, carol } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig,
+        carol: mainConfig
+      },
+      true
+    );
+
+    const aliceAddress = alice.instance("badges_instance").agentAddress;
+    const bobAddress = bob.instance("badges_instance").agentAddress;
+    const carolAddress = carol.instance("badges_instance").agentAddress;
+
+    const { Ok: rustClassAddress } = await createBadgeClass({
+      ...testBadgeClass,
+      name: "Rust",
+      validators: 1
+    })(alice);
+    const { Ok: wasmClassAddress } = await createBadgeClass({
+      ...testBadgeClass,
+      name: "WebAssembly",
+      validators: 1
+    })(alice);
+    const { Ok: tentativeClassAddress } = await createBadgeClass({
+      ...testBadgeClass,
+      name: "Tentative"
+    })(alice);
+    await s.consistency();
+
+    const { Ok: rustBadgeAddress } = await claimAgentDeservesBadge(
+      bobAddress,
+      rustClassAddress
+    )(alice);
+    const { Ok: wasmBadgeAddress } = await claimAgentDeservesBadge(
+      bobAddress,
+      wasmClassAddress
+    )(alice);
+    const { Ok: tentativeBadgeAddress } = await claimAgentDeservesBadge(
+      bobAddress,
+      tentativeClassAddress
+    )(alice);
+    const { Ok: carolBadgeAddress } = await claimAgentDeservesBadge(
+      carolAddress,
+      rustClassAddress
+    )(alice);
+    await s.consistency();
+
+    // Only completed badges held by the owner can be collected
+    let result = await bob.call("badges_instance", "badges", "create_collection", {
+      name: "Rust skills",
+      badges: [rustBadgeAddress, carolBadgeAddress]
+    });
+    t.notOk(result.Ok);
+
+    result = await bob.call("badges_instance", "badges", "create_collection", {
+      name: "Rust skills",
+      badges: [rustBadgeAddress, tentativeBadgeAddress]
+    });
+    t.notOk(result.Ok);
+
+    const { Ok: collectionAddress } = await bob.call(
+      "badges_instance",
+      "badges",
+      "create_collection",
+      {
+        name: "Rust skills",
+        badges: [rustBadgeAddress, wasmBadgeAddress]
+      }
+    );
+    t.ok(collectionAddress);
+    await s.consistency();
+
+    result = await bob.call("badges_instance", "badges", "reorder_collection", {
+      collection: collectionAddress,
+      badges: [wasmBadgeAddress, rustBadgeAddress]
+    });
+    const reorderedAddress = result.Ok;
+    t.ok(reorderedAddress);
+    await s.consistency();
+
+    result = await carol.call(
+      "badges_instance",
+      "badges",
+      "export_open_badges_collection",
+      { collection: collectionAddress }
+    );
+    t.equal(result.Ok.name, "Rust skills");
+    t.deepEqual(
+      result.Ok.assertions.map(assertion => assertion.badge.name),
+      ["WebAssembly", "Rust"]
+    );
+    t.equal(result.Ok.assertions[0].type, "Assertion");
+    t.ok(result.Ok.assertions[0].issuedOn);
+
+    // Only the owner can change a collection
+    result = await carol.call("badges_instance", "badges", "share_collection", {
+      collection: collectionAddress,
+      visibility: { type: "hidden" }
+    });
+    t.notOk(result.Ok);
+
+    // Sharing through any version of the collection grants access to the collection itself
+    result = await bob.call("badges_instance", "badges", "share_collection", {
+      collection: reorderedAddress,
+      visibility: { type: "shared", agents: [aliceAddress] }
+    });
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await carol.call("badges_instance", "badges", "get_collections", {
+      agent_address: bobAddress
+    });
+    t.deepEqual(result.Ok, []);
+
+    result = await alice.call("badges_instance", "badges", "get_collections", {
+      agent_address: bobAddress
+    });
+    t.deepEqual(result.Ok, [collectionAddress]);
+
+    result = await carol.call(
+      "badges_instance",
+      "badges",
+      "export_open_badges_collection",
+      { collection: collectionAddress }
+    );
+    t.notOk(result.Ok);
+
+    result = await alice.call(
+      "badges_instance",
+      "badges",
+      "export_open_badges_collection",
+      { collection: collectionAddress }
+    );
+    t.equal(result.Ok.assertions.length, 2);
+  }
+);
+
+orchestrator.registerScenario(
+  "recipients and creators are notified of claims",
+  async (s, t) => {
+    const { alice, bob, carol } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig,
+        carol: mainConfig
+      },
+      true
+    );
+
+    const aliceAddress = alice.instance("badges_instance").agentAddress;
+    const bobAddress = bob.instance("badges_instance").agentAddress;
+    const carolAddress = carol.instance("badges_instance").agentAddress;
+
+    const { Ok: badgeClassAddress } = await createBadgeClass({
+      ...testBadgeClass,
+      validators: 1
+    })(alice);
+    await s.consistency();
+
+    const { Ok: badgeAddress } = await claimAgentDeservesBadge(
+      bobAddress,
+      badgeClassAddress
+    )(alice);
+    await s.consistency();
+
+    let result = await bob.call("badges_instance", "badges", "get_notifications", {});
+    t.deepEqual(
+      result.Ok.map(({ notification }) => notification.message),
+      [
+        {
+          type: "badge-claimed",
+          badge: badgeAddress,
+          badge_class: badgeClassAddress,
+          issuer: aliceAddress
+        },
+        {
+          type: "badge-completed",
+          badge: badgeAddress,
+          badge_class: badgeClassAddress
+        }
+      ]
+    );
+    t.equal(result.Ok[0].notification.sender, aliceAddress);
+
+    // Bob now holds the badge and can claim it for carol
+    await claimAgentDeservesBadge(carolAddress, badgeClassAddress)(bob);
+    await s.consistency();
+
+    result = await alice.call("badges_instance", "badges", "get_notifications", {});
+    t.deepEqual(result.Ok[0].notification.message, {
+      type: "class-claimed",
+      badge: result.Ok[0].notification.message.badge,
+      badge_class: badgeClassAddress,
+      recipient: carolAddress,
+      issuer: bobAddress
+    });
+
+    // Only notifications can be marked as read
+    result = await bob.call("badges_instance", "badges", "mark_notification_read", {
+      notification: badgeAddress
+    });
+    t.notOk(result.Ok);
+
+    // Read notifications leave the inbox
+    result = await bob.call("badges_instance", "badges", "get_notifications", {});
+    await bob.call("badges_instance", "badges", "mark_notification_read", {
+      notification: result.Ok[0].address
+    });
+
+    result = await bob.call("badges_instance", "badges", "get_notifications", {});
+    t.equal(result.Ok.length, 1);
+    t.equal(result.Ok[0].notification.message.type, "badge-completed");
+  }
+);
+
+orchestrator.registerScenario(
+  "issuance report detects rings, shared upstreams and bursts",
+  async (s, t) => {
+    const { alice, bob, carol, dave, eve, frank, george } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig,
+        carol: mainConfig,
+        dave: mainConfig,
+        eve: mainConfig,
+        frank: mainConfig,
+        george: mainConfig
+      },
+      true
+    );
+
+    const aliceAddress = alice.instance("badges_instance").agentAddress;
+    const bobAddress = bob.instance("badges_instance").agentAddress;
+    const carolAddress = carol.instance("badges_instance").agentAddress;
+    const georgeAddress = george.instance("badges_instance").agentAddress;
+    const burstRecipients = [bob, carol, dave, eve, frank].map(
+      player => player.instance("badges_instance").agentAddress
+    );
+
+    const { Ok: badgeClassAddress } = await createBadgeClass({
+      ...testBadgeClass,
+      validators: 1
+    })(alice);
+    await s.consistency();
+
+    // Alice claims five badges in a row
+    for (const recipient of burstRecipients) {
+      await claimAgentDeservesBadge(recipient, badgeClassAddress)(alice);
+    }
+    await s.consistency();
+
+    // The rest of the claims are made under a new version of the class
+    const { Ok: secondVersion } = await alice.call(
+      "badges_instance",
+      "badges",
+      "create_badge_class_version",
+      {
+        ...testBadgeClass,
+        validators: 1,
+        previous_version: badgeClassAddress
+      }
+    );
+    await s.consistency();
+
+    // Bob and carol vouch for each other
+    await claimAgentDeservesBadge(carolAddress, secondVersion)(bob);
+    await claimAgentDeservesBadge(bobAddress, secondVersion)(carol);
+    await s.consistency();
+
+    // George gets the badge from bob and carol, who both got it from alice
+    await claimAgentDeservesBadge(georgeAddress, secondVersion)(bob);
+    await claimAgentDeservesBadge(georgeAddress, secondVersion)(carol);
+    await s.consistency();
+
+    const result = await alice.call(
+      "badges_instance",
+      "badges",
+      "get_issuance_report",
+      {
+        badge_class: badgeClassAddress
+      }
+    );
+    const report = result.Ok;
+
+    t.equal(report.issuance_rings.length, 1);
+    t.deepEqual(
+      [...report.issuance_rings[0]].sort(),
+      [bobAddress, carolAddress].sort()
+    );
+
+    t.equal(report.shared_upstream.length, 1);
+    t.equal(report.shared_upstream[0].agent, georgeAddress);
+    t.deepEqual(
+      [...report.shared_upstream[0].issuers].sort(),
+      [bobAddress, carolAddress].sort()
+    );
+    t.deepEqual(report.shared_upstream[0].upstream_issuers, [aliceAddress]);
+
+    t.equal(report.issuance_bursts.length, 1);
+    t.equal(report.issuance_bursts[0].issuer, aliceAddress);
+    t.deepEqual(
+      [...report.issuance_bursts[0].recipients].sort(),
+      [...burstRecipients].sort()
+    );
+  }
+);
+
+orchestrator.registerScenario(
+  "claims must come from independent lineages",
+  async (s, t) => {
+    const { alice, bob, carol, dave, eve, frank } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig,
+        carol: mainConfig,
+        dave: mainConfig,
+        eve: mainConfig,
+        frank: mainConfig
+      },
+      true
+    );
+
+    const bobAddress = bob.instance("badges_instance").agentAddress;
+    const carolAddress = carol.instance("badges_instance").agentAddress;
+    const daveAddress = dave.instance("badges_instance").agentAddress;
+    const eveAddress = eve.instance("badges_instance").agentAddress;
+    const frankAddress = frank.instance("badges_instance").agentAddress;
+
+    const { Ok: firstVersion } = await createBadgeClass({
+      ...testBadgeClass,
+      validators: 1
+    })(alice);
+    await s.consistency();
+
+    // Bob and frank get the badge from alice, carol and dave both get it from bob
+    await claimAgentDeservesBadge(bobAddress, firstVersion)(alice);
+    await claimAgentDeservesBadge(frankAddress, firstVersion)(alice);
+    await s.consistency();
+    await claimAgentDeservesBadge(carolAddress, firstVersion)(bob);
+    await claimAgentDeservesBadge(daveAddress, firstVersion)(bob);
+    await s.consistency();
+
+    let result = await alice.call(
+      "badges_instance",
+      "badges",
+      "create_badge_class_version",
+      {
+        ...testBadgeClass,
+        validators: 2,
+        min_lineages: 2,
+        previous_version: firstVersion
+      }
+    );
+    const secondVersion = result.Ok;
+    t.ok(secondVersion);
+    await s.consistency();
+
+    // Carol and dave both derive from bob: their two claims only count as one lineage
+    result = await claimAgentDeservesBadge(eveAddress, secondVersion)(carol);
+    t.ok(result.Ok);
+    result = await claimAgentDeservesBadge(eveAddress, secondVersion)(dave);
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await eve.call(
+      "badges_instance",
+      "badges",
+      "get_badges_to_recipient",
+      { agent_address: eveAddress }
+    );
+    t.deepEqual(result.Ok, []);
+
+    result = await eve.call(
+      "badges_instance",
+      "badges",
+      "get_temptative_badges_to_recipient",
+      { agent_address: eveAddress }
+    );
+    t.equal(result.Ok.length, 1);
+
+    // Eve does not hold the badge yet, so she cannot issue it
+    result = await claimAgentDeservesBadge(bobAddress, secondVersion)(eve);
+    t.notOk(result.Ok);
+
+    // Frank got the badge directly from alice, which makes a second lineage
+    result = await claimAgentDeservesBadge(eveAddress, secondVersion)(frank);
+    t.ok(result.Ok);
+    await s.consistency();
+
+    result = await eve.call(
+      "badges_instance",
+      "badges",
+      "get_badges_to_recipient",
+      { agent_address: eveAddress }
+    );
+    t.equal(result.Ok.length, 1);
+  }
+);
+
+orchestrator.registerScenario(
+  "badge classes are listed across the shards of the index",
+  async (s, t) => {
+    const { alice, bob } = await s.players(
+      {
+        alice: mainConfig,
+        bob: mainConfig
+      },
+      true
+    );
+
+    const badgeClasses = [];
+    for (let i = 0; i < 10; i++) {
+      const { Ok: badgeClassAddress } = await createBadgeClass({
+        ...testBadgeClass,
+        name: `Sharded badge ${i}`
+      })(alice);
+      badgeClasses.push(badgeClassAddress);
+    }
+    await s.consistency();
+
+    // Classes are indexed by the first two characters of their address after "Qm"
+    const shards = new Set(badgeClasses.map(address => address.slice(2, 4)));
+    t.ok(shards.size > 1);
+
+    const result = await bob.call(
+      "badges_instance",
+      "badges",
+      "get_all_badge_classes",
+      {}
+    );
+    t.equal(result.Ok.length, badgeClasses.length);
+    t.deepEqual([...result.Ok].sort(), [...badgeClasses].sort());
+  }
+);
+
 orchestrator.run();
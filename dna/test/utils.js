@@ -2,7 +2,16 @@ let testBadgeClass = {
   name: "Test badge",
   description: "Test description",
   image: "Test image",
-  validators: 2
+  validators: 2,
+  min_lineages: null,
+  issuance_policy: "holders",
+  max_holders: null,
+  issuance_opens_at: null,
+  issuance_closes_at: null,
+  kind: { type: "claims" },
+  criteria: [],
+  alignments: [],
+  tags: []
 };
 
 function createBadgeClass(badgeClass = testBadgeClass) {
@@ -10,12 +19,23 @@ function createBadgeClass(badgeClass = testBadgeClass) {
     caller.call("badges_instance", "badges", "create_badge_class", badgeClass);
 }
 
-function claimAgentDeservesBadge(recipient, badgeClass) {
+function claimAgentDeservesBadge(
+  recipient,
+  badgeClass,
+  statement = "Test statement",
+  confidence = "high",
+  criteria = [],
+  onBehalfOf = null
+) {
   return caller =>
     caller.call("badges_instance", "badges", "claim_agent_deserves_badge", {
       recipient: recipient,
       badge_class: badgeClass,
-      evidences: []
+      evidences: [],
+      statement,
+      confidence,
+      criteria,
+      on_behalf_of: onBehalfOf
     });
 }
 
@@ -0,0 +1,90 @@
+use crate::anchor;
+use crate::badge_class::{self, BadgeClass};
+use hdk::prelude::*;
+
+/**
+ * Alignment of a badge class to a competency of an external framework, following the Open Badges AlignmentObject
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Alignment {
+    pub target_name: String,
+    pub target_url: String,
+    pub target_description: Option<String>,
+    pub target_framework: Option<String>,
+    pub target_code: Option<String>,
+}
+
+/** Handlers */
+
+/**
+ * Returns the latest versions of the badge classes aligned to the given competency, leaving out the classes
+ * whose latest version dropped the alignment
+ */
+pub fn get_badge_classes_by_alignment(
+    framework: String,
+    code: String,
+) -> ZomeApiResult<Vec<Address>> {
+    let links = hdk::get_links(
+        &anchors::anchor_address::<anchor::BadgeAnchors>(
+            anchor::ALIGNMENT_ANCHOR_TYPE,
+            &anchor::alignment_text(&framework, &code),
+        )?,
+        LinkMatch::Exactly("alignment->badge_class"),
+        LinkMatch::Any,
+    )?;
+
+    let mut badge_classes: Vec<Address> = vec![];
+    for address in links.addresses() {
+        let latest_version = badge_class::get_latest_version(&address)?;
+        let latest_class: BadgeClass = hdk::utils::get_as_type(latest_version.clone())?;
+
+        let still_aligned = latest_class.alignments.iter().any(|alignment| {
+            alignment.target_framework.as_ref() == Some(&framework)
+                && alignment.target_code.as_ref() == Some(&code)
+        });
+
+        if still_aligned && !badge_classes.contains(&latest_version) {
+            badge_classes.push(latest_version);
+        }
+    }
+
+    Ok(badge_classes)
+}
+
+/** Helpers */
+
+/**
+ * Links the badge class from the anchors of all the competencies it is aligned to
+ */
+pub fn index_alignments(class_address: &Address, class: &BadgeClass) -> ZomeApiResult<()> {
+    for alignment in class.alignments.iter() {
+        if let (Some(framework), Some(code)) = (&alignment.target_framework, &alignment.target_code)
+        {
+            hdk::link_entries(
+                &anchors::anchor::<anchor::BadgeAnchors>(
+                    anchor::ALIGNMENT_ANCHOR_TYPE,
+                    &anchor::alignment_text(framework, code),
+                )?,
+                class_address,
+                "alignment->badge_class",
+                "",
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/** Validation helpers */
+
+pub fn assert_alignments_valid(alignments: &Vec<Alignment>) -> ZomeApiResult<()> {
+    for alignment in alignments {
+        if alignment.target_name.is_empty() || alignment.target_url.is_empty() {
+            return Err(ZomeApiError::from(String::from(
+                "Alignments must have a target name and a target url",
+            )));
+        }
+    }
+
+    Ok(())
+}
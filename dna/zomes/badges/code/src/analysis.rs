@@ -0,0 +1,260 @@
+use crate::badge::Badge;
+use crate::badge_class;
+use crate::class_admins;
+use crate::lineage::get_issued_badges;
+use crate::organization::OrganizationRole;
+use crate::timestamp::seconds;
+use hdk::holochain_core_types::time::Iso8601;
+use hdk::prelude::*;
+use std::collections::HashMap;
+
+/** Number of claims from the same issuer that make up a burst */
+const BURST_MIN_CLAIMS: usize = 5;
+/** Time window in which `BURST_MIN_CLAIMS` claims are considered a burst */
+const BURST_WINDOW_SECONDS: i64 = 60 * 60;
+
+/**
+ * Report of suspicious issuance patterns among the holders of a badge class
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct IssuanceReport {
+    pub badge_class: Address,
+    pub issuance_rings: Vec<Vec<Address>>,
+    pub shared_upstream: Vec<SharedUpstream>,
+    pub issuance_bursts: Vec<IssuanceBurst>,
+}
+
+/**
+ * Agent with more than one issuer, all of them having received the badge from the same upstream issuers
+ */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SharedUpstream {
+    pub agent: Address,
+    pub issuers: Vec<Address>,
+    pub upstream_issuers: Vec<Address>,
+}
+
+/**
+ * Sequence of at least `BURST_MIN_CLAIMS` claims made by the same issuer within `BURST_WINDOW_SECONDS`
+ */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IssuanceBurst {
+    pub issuer: Address,
+    pub recipients: Vec<Address>,
+    pub from: Iso8601,
+    pub to: Iso8601,
+}
+
+/** Handlers */
+
+/**
+ * Analyzes the issuance of all the versions of the badge class, checking issuers against the admins of the latest one
+ */
+pub fn get_issuance_report(badge_class: Address) -> ZomeApiResult<IssuanceReport> {
+    let versions = badge_class::get_class_versions(&badge_class)?;
+    let admins = class_admins::get_class_admins(&badge_class::get_latest_version(&badge_class)?)?;
+    let graph = get_issuance_graph(&versions)?;
+
+    let mut admin_issuers: Vec<Address> = vec![];
+    for agent in graph.keys() {
+        if admins.acts_for(agent, OrganizationRole::Issuer)? {
+            admin_issuers.push(agent.clone());
+        }
+    }
+
+    let mut issuance_bursts: Vec<IssuanceBurst> = vec![];
+    for issuer in graph.keys().cloned().collect::<Vec<Address>>() {
+        if let Some(burst) = find_issuance_burst(&issuer, &versions)? {
+            issuance_bursts.push(burst);
+        }
+    }
+
+    Ok(IssuanceReport {
+        badge_class,
+        issuance_rings: find_issuance_rings(&graph),
+        shared_upstream: find_shared_upstream(&graph, &admin_issuers),
+        issuance_bursts,
+    })
+}
+
+/** Helpers */
+
+/**
+ * Returns the issuers of every holder of the given versions of a badge class, indexed by holder
+ */
+fn get_issuance_graph(versions: &Vec<Address>) -> ZomeApiResult<HashMap<Address, Vec<Address>>> {
+    let mut badges: Vec<Address> = vec![];
+    for version in versions {
+        let links = hdk::get_links(
+            version,
+            LinkMatch::Exactly("badge_class->badge"),
+            LinkMatch::Any,
+        )?;
+
+        badges.append(&mut links.addresses());
+    }
+
+    let mut graph: HashMap<Address, Vec<Address>> = HashMap::new();
+
+    for address in badges {
+        let badge: Badge = hdk::utils::get_as_type(address)?;
+
+        // Every version of the badge is linked from the class, each with the issuers up to then
+        let issuers = graph.entry(badge.recipient.clone()).or_insert(vec![]);
+        for issuer in badge.issuers.iter() {
+            if !issuers.contains(issuer) {
+                issuers.push(issuer.clone());
+            }
+        }
+
+        for issuer in badge.issuers {
+            graph.entry(issuer).or_insert(vec![]);
+        }
+    }
+
+    Ok(graph)
+}
+
+/**
+ * Finds the strongly connected components of the issuance graph with more than one agent,
+ * using Tarjan's algorithm
+ *
+ * The depth-first search keeps its own stack of visits instead of recursing,
+ * since long issuance chains would otherwise overflow the stack of the zome
+ */
+fn find_issuance_rings(graph: &HashMap<Address, Vec<Address>>) -> Vec<Vec<Address>> {
+    let mut index: usize = 0;
+    let mut indexes: HashMap<Address, usize> = HashMap::new();
+    let mut lowlinks: HashMap<Address, usize> = HashMap::new();
+    let mut stack: Vec<Address> = vec![];
+    let mut rings: Vec<Vec<Address>> = vec![];
+
+    for root in graph.keys() {
+        if indexes.contains_key(root) {
+            continue;
+        }
+
+        // Each visit is the agent being visited and the position of the next issuer to follow
+        let mut visits: Vec<(Address, usize)> = vec![(root.clone(), 0)];
+        indexes.insert(root.clone(), index);
+        lowlinks.insert(root.clone(), index);
+        index += 1;
+        stack.push(root.clone());
+
+        while let Some((agent, next_issuer)) = visits.pop() {
+            let issuers = graph.get(&agent).cloned().unwrap_or(vec![]);
+
+            if let Some(issuer) = issuers.get(next_issuer) {
+                visits.push((agent.clone(), next_issuer + 1));
+
+                if !indexes.contains_key(issuer) {
+                    indexes.insert(issuer.clone(), index);
+                    lowlinks.insert(issuer.clone(), index);
+                    index += 1;
+                    stack.push(issuer.clone());
+                    visits.push((issuer.clone(), 0));
+                } else if stack.contains(issuer) {
+                    let lowlink = lowlinks[&agent].min(indexes[issuer]);
+                    lowlinks.insert(agent.clone(), lowlink);
+                }
+
+                continue;
+            }
+
+            // All the issuers of the agent have been visited
+            if lowlinks[&agent] == indexes[&agent] {
+                let mut component: Vec<Address> = vec![];
+                while let Some(member) = stack.pop() {
+                    let is_root = member == agent;
+                    component.push(member);
+                    if is_root {
+                        break;
+                    }
+                }
+
+                if component.len() > 1 {
+                    rings.push(component);
+                }
+            }
+
+            if let Some((parent, _)) = visits.last() {
+                let lowlink = lowlinks[parent].min(lowlinks[&agent]);
+                lowlinks.insert(parent.clone(), lowlink);
+            }
+        }
+    }
+
+    rings
+}
+
+/**
+ * Finds the agents with more than one issuer whose issuers all got the badge from the same upstream issuers,
+ * skipping the agents that received the badge from someone issuing as the owner or an administrator of the class
+ */
+fn find_shared_upstream(
+    graph: &HashMap<Address, Vec<Address>>,
+    admin_issuers: &Vec<Address>,
+) -> Vec<SharedUpstream> {
+    let mut shared_upstream: Vec<SharedUpstream> = vec![];
+
+    for (agent, issuers) in graph.iter() {
+        if issuers.len() < 2 || issuers.iter().any(|issuer| admin_issuers.contains(issuer)) {
+            continue;
+        }
+
+        let mut upstream_issuers: Vec<Address> = graph.get(&issuers[0]).cloned().unwrap_or(vec![]);
+        for issuer in issuers.iter().skip(1) {
+            let issuer_upstream = graph.get(issuer).cloned().unwrap_or(vec![]);
+            upstream_issuers.retain(|upstream| issuer_upstream.contains(upstream));
+        }
+
+        if !upstream_issuers.is_empty() {
+            shared_upstream.push(SharedUpstream {
+                agent: agent.clone(),
+                issuers: issuers.clone(),
+                upstream_issuers,
+            });
+        }
+    }
+
+    shared_upstream
+}
+
+/**
+ * Returns the first window of `BURST_WINDOW_SECONDS` in which the issuer made at least `BURST_MIN_CLAIMS` claims
+ * under any of the given versions of a badge class
+ */
+fn find_issuance_burst(issuer: &Address, versions: &Vec<Address>) -> ZomeApiResult<Option<IssuanceBurst>> {
+    let mut claims: Vec<(Iso8601, Address)> = vec![];
+
+    for (badge_address, issued_at) in get_issued_badges(issuer, versions)? {
+        if let Some(timestamp) = issued_at {
+            let badge: Badge = hdk::utils::get_as_type(badge_address)?;
+            claims.push((timestamp, badge.recipient));
+        }
+    }
+
+    claims.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for start in 0..claims.len() {
+        let from = seconds(&claims[start].0)?;
+        let window: Vec<&(Iso8601, Address)> = claims[start..]
+            .iter()
+            .take_while(|(timestamp, _)| match seconds(timestamp) {
+                Ok(to) => to - from <= BURST_WINDOW_SECONDS,
+                Err(_) => false,
+            })
+            .collect();
+
+        if window.len() >= BURST_MIN_CLAIMS {
+            return Ok(Some(IssuanceBurst {
+                issuer: issuer.clone(),
+                recipients: window.iter().map(|(_, recipient)| recipient.clone()).collect(),
+                from: window[0].0.clone(),
+                to: window[window.len() - 1].0.clone(),
+            }));
+        }
+    }
+
+    Ok(None)
+}
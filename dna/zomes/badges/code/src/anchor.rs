@@ -1,47 +1,162 @@
+use crate::tag;
+use anchors::{Anchor, AnchorValidator};
 use hdk::prelude::*;
 
+/** Entry type of the anchors indexing badge classes by shard, tag, search token and alignment */
+pub const ANCHOR_ENTRY_TYPE: &str = "badge_anchor";
+/** Entry type of the plain string anchor that older versions committed */
+pub const LEGACY_ANCHOR_ENTRY_TYPE: &str = "anchor";
+
+/** Anchors of the shards of the badge class index, with the first characters of the class addresses as text */
+pub const SHARD_ANCHOR_TYPE: &str = "badge_class_shard";
+pub const TAG_ANCHOR_TYPE: &str = "tag";
+pub const SEARCH_ANCHOR_TYPE: &str = "search";
+pub const ALIGNMENT_ANCHOR_TYPE: &str = "alignment";
+
+/** Text of the anchor that older versions linked directly to all badge classes */
+const LEGACY_ROOT_ANCHOR_TEXT: &str = "all_badges_classes";
+/** Number of characters of the class address that determine its shard */
+const SHARD_PREFIX_LENGTH: usize = 2;
+
+/**
+ * Restricts the anchors of this zome to the known types, with well formed texts
+ */
+pub struct BadgeAnchors;
+
+impl AnchorValidator for BadgeAnchors {
+    const ENTRY_TYPE: &'static str = ANCHOR_ENTRY_TYPE;
+
+    fn validate_anchor(anchor: &Anchor) -> Result<(), String> {
+        let text = anchor.anchor_text.clone().unwrap_or_default();
+
+        let valid = match anchor.anchor_type.as_str() {
+            SHARD_ANCHOR_TYPE => text.chars().count() == SHARD_PREFIX_LENGTH,
+            TAG_ANCHOR_TYPE => !text.is_empty() && tag::normalize_tag(&text) == text,
+            SEARCH_ANCHOR_TYPE => !text.is_empty() && text.to_lowercase() == text,
+            ALIGNMENT_ANCHOR_TYPE => !text.is_empty(),
+            _ => false,
+        };
+
+        match valid {
+            true => Ok(()),
+            false => Err(format!(
+                "Invalid anchor {} of type {}",
+                text, anchor.anchor_type
+            )),
+        }
+    }
+}
+
 pub fn entry_def() -> ValidatingEntryType {
+    anchors::anchor_definition::<BadgeAnchors>()
+}
+
+/**
+ * Plain string anchor of older versions, kept so that the classes linked from it can still be read and validated
+ */
+pub fn legacy_entry_def() -> ValidatingEntryType {
     entry!(
-        name: "anchor",
-        description: "Anchor to all badge classes",
+        name: LEGACY_ANCHOR_ENTRY_TYPE,
+        description: "Anchor to all badge classes, as committed by older versions",
         sharing: Sharing::Public,
         validation_package: || {
             hdk::ValidationPackageDefinition::Entry
         },
         validation: |validation_data: hdk::EntryValidationData<String>| {
             match validation_data {
-                EntryValidationData::Create { .. } => {
-                    Ok(())
+                EntryValidationData::Create { entry, .. } => {
+                    match entry == LEGACY_ROOT_ANCHOR_TEXT {
+                        true => Ok(()),
+                        false => Err(format!("Invalid legacy anchor {}", entry)),
+                    }
                 },
                 _ => Err(String::from("Cannot update or delete an anchor")),
             }
         },
-        links: [
-            to!(
-                "badge_class",
-                link_type: "anchor->badge_class",
-                validation_package: || {
-                    hdk::ValidationPackageDefinition::Entry
-                },
-                validation: | validation_data: hdk::LinkValidationData | {
-                    match validation_data {
-                        hdk::LinkValidationData::LinkAdd { .. } => Ok(()),
-                        _ => Err(String::from("Cannot delete links"))
-                    }
-                }
-            )
-        ]
+        links: []
     )
 }
 
-pub fn address() -> ZomeApiResult<Address> {
-    let entry = Entry::App("anchor".into(), "all_badges_classes".into());
+/**
+ * Iterates over all badge classes, walking first the classes linked from the legacy root anchor
+ * and then the classes of every shard, fetching each shard only when it is reached
+ */
+pub struct BadgeClassIterator {
+    shards: std::vec::IntoIter<Address>,
+    current: std::vec::IntoIter<Address>,
+}
+
+impl Iterator for BadgeClassIterator {
+    type Item = ZomeApiResult<Address>;
 
-    let anchor_address = hdk::entry_address(&entry)?;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(badge_class) = self.current.next() {
+                return Some(Ok(badge_class));
+            }
 
-    if let None = hdk::get_entry(&anchor_address)? {
-        hdk::commit_entry(&entry)?;
+            let shard = self.shards.next()?;
+            match hdk::get_links(
+                &shard,
+                LinkMatch::Exactly("shard->badge_class"),
+                LinkMatch::Any,
+            ) {
+                Ok(links) => self.current = links.addresses().into_iter(),
+                Err(error) => return Some(Err(error)),
+            }
+        }
     }
+}
+
+pub fn badge_classes() -> ZomeApiResult<BadgeClassIterator> {
+    let legacy_links = hdk::get_links(
+        &legacy_root_address()?,
+        LinkMatch::Exactly("anchor->badge_class"),
+        LinkMatch::Any,
+    )?;
+
+    Ok(BadgeClassIterator {
+        shards: anchors::list_anchors::<BadgeAnchors>(SHARD_ANCHOR_TYPE)?.into_iter(),
+        current: legacy_links.addresses().into_iter(),
+    })
+}
+
+/**
+ * Links the badge class from the anchor of its shard
+ */
+pub fn link_badge_class(class_address: &Address) -> ZomeApiResult<()> {
+    let shard_address = anchors::anchor::<BadgeAnchors>(SHARD_ANCHOR_TYPE, &shard(class_address))?;
+
+    hdk::link_entries(&shard_address, class_address, "shard->badge_class", "")?;
+
+    Ok(())
+}
+
+pub fn shard(class_address: &Address) -> String {
+    class_address
+        .to_string()
+        .chars()
+        .skip(2) // Every multihash starts with "Qm"
+        .take(SHARD_PREFIX_LENGTH)
+        .collect()
+}
+
+/**
+ * Text of the anchor of a competency, prefixed with the length of the framework so that
+ * frameworks and codes containing the separator cannot collide
+ */
+pub fn alignment_text(framework: &String, code: &String) -> String {
+    format!("{}:{}:{}", framework.len(), framework, code)
+}
+
+/**
+ * Address of the untyped anchor that older versions of this zome committed, to keep their classes visible
+ */
+pub fn legacy_root_address() -> ZomeApiResult<Address> {
+    let entry = Entry::App(
+        LEGACY_ANCHOR_ENTRY_TYPE.into(),
+        String::from(LEGACY_ROOT_ANCHOR_TEXT).into(),
+    );
 
-    Ok(anchor_address)
+    hdk::entry_address(&entry)
 }
@@ -0,0 +1,336 @@
+use crate::attestation::Confidence;
+use crate::badge;
+use crate::badge_class::{self, BadgeClass};
+use hdk::prelude::*;
+use hdk::AGENT_ADDRESS;
+
+/**
+ * Request from an agent to receive a badge, to be endorsed or rejected by its issuers
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct BadgeApplication {
+    pub applicant: Address,
+    pub badge_class: Address,
+    pub evidences: Vec<Address>,
+    pub statement: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct ApplicationReview {
+    pub application: Address,
+    pub reviewer: Address,
+    pub endorsed: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ApplicationStatus {
+    Pending,
+    Rejected,
+    Completed,
+}
+
+/**
+ * An application together with the issuers that reviewed it and its resulting status
+ */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApplicationWithStatus {
+    pub address: Address,
+    pub application: BadgeApplication,
+    pub endorsed_by: Vec<Address>,
+    pub rejected_by: Vec<Address>,
+    pub status: ApplicationStatus,
+}
+
+pub fn application_entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "badge_application",
+        description: "An application of an agent to receive a badge",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: |validation_data: hdk::EntryValidationData<BadgeApplication>| {
+            match validation_data {
+                EntryValidationData::Create { entry, validation_data } => {
+                    if !validation_data.sources().contains(&entry.applicant) {
+                        return Err(String::from("Only the applicant can apply for a badge"));
+                    }
+
+                    hdk::utils::get_as_type::<BadgeClass>(entry.badge_class.clone())?;
+
+                    Ok(())
+                },
+                _ => Err(String::from("Cannot update or delete a badge application")),
+            }
+        },
+        links: [
+            from!(
+                "badge_class",
+                link_type: "badge_class->badge_application",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, .. } => {
+                            let application: BadgeApplication = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            match application.badge_class == link.link.base().clone() {
+                                true => Ok(()),
+                                false => Err(String::from("Cannot link \"badge_class->badge_application\" to an application for another badge class"))
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            ),
+            from!(
+                "%agent_id",
+                link_type: "applicant->badge_application",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, .. } => {
+                            let application: BadgeApplication = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            match application.applicant == link.link.base().clone() {
+                                true => Ok(()),
+                                false => Err(String::from("Cannot link \"applicant->badge_application\" from an agent who is not the applicant"))
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            )
+        ]
+    )
+}
+
+pub fn review_entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "application_review",
+        description: "The endorsement or rejection of a badge application by an issuer",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: |validation_data: hdk::EntryValidationData<ApplicationReview>| {
+            match validation_data {
+                EntryValidationData::Create { entry, validation_data } => {
+                    if !validation_data.sources().contains(&entry.reviewer) {
+                        return Err(String::from("Only the reviewer can sign the review of an application"));
+                    }
+
+                    let application: BadgeApplication = hdk::utils::get_as_type(entry.application.clone())?;
+
+                    if application.applicant == entry.reviewer {
+                        return Err(String::from("Applicants cannot review their own applications"));
+                    }
+
+                    badge::assert_issuer_valid_at(&application.badge_class, &entry.reviewer, validation_data.package.chain_header.timestamp())?;
+
+                    Ok(())
+                },
+                _ => Err(String::from("Cannot update or delete an application review")),
+            }
+        },
+        links: [
+            from!(
+                "badge_application",
+                link_type: "badge_application->review",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, .. } => {
+                            let review: ApplicationReview = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            match review.application == link.link.base().clone() {
+                                true => Ok(()),
+                                false => Err(String::from("Cannot link \"badge_application->review\" to a review of another application"))
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            )
+        ]
+    )
+}
+
+/** Handlers */
+
+pub fn apply_for_badge(
+    badge_class: Address,
+    evidences: Vec<Address>,
+    statement: String,
+) -> ZomeApiResult<Address> {
+    let application = BadgeApplication {
+        applicant: AGENT_ADDRESS.clone(),
+        badge_class: badge_class.clone(),
+        evidences,
+        statement,
+    };
+
+    let application_entry = Entry::App("badge_application".into(), application.into());
+    let application_address = hdk::commit_entry(&application_entry)?;
+
+    hdk::link_entries(
+        &badge_class,
+        &application_address,
+        "badge_class->badge_application",
+        "",
+    )?;
+    hdk::link_entries(
+        &AGENT_ADDRESS,
+        &application_address,
+        "applicant->badge_application",
+        "",
+    )?;
+
+    Ok(application_address)
+}
+
+/**
+ * Endorses the application, claiming that the applicant deserves the badge,
+ * and only records the endorsement once the claim has been made
+ */
+pub fn endorse_application(
+    application_address: Address,
+    statement: String,
+    confidence: Confidence,
+    criteria: Vec<String>,
+) -> ZomeApiResult<Address> {
+    let application: BadgeApplication = hdk::utils::get_as_type(application_address.clone())?;
+
+    let badge_address = badge::claim_agent_deserves_badge(
+        application.applicant,
+        application.badge_class,
+        application.evidences,
+        statement,
+        confidence,
+        criteria,
+        None,
+    )?;
+
+    review_application(&application_address, true)?;
+
+    Ok(badge_address)
+}
+
+pub fn reject_application(application_address: Address) -> ZomeApiResult<Address> {
+    review_application(&application_address, false)
+}
+
+/**
+ * Returns the applications for any version of the given class that are still pending
+ * and have not been reviewed yet by the calling agent, which must be able to issue the latest version of the class
+ */
+pub fn get_pending_applications(badge_class: Address) -> ZomeApiResult<Vec<ApplicationWithStatus>> {
+    badge::assert_issuer_valid(
+        &badge_class::get_latest_version(&badge_class)?,
+        &AGENT_ADDRESS,
+    )?;
+
+    let mut pending: Vec<ApplicationWithStatus> = vec![];
+    for version in badge_class::get_class_versions(&badge_class)? {
+        let links = hdk::get_links(
+            &version,
+            LinkMatch::Exactly("badge_class->badge_application"),
+            LinkMatch::Any,
+        )?;
+
+        for address in links.addresses() {
+            let application = get_application_with_status(address)?;
+
+            let reviewed = application.endorsed_by.contains(&AGENT_ADDRESS)
+                || application.rejected_by.contains(&AGENT_ADDRESS);
+
+            if application.status == ApplicationStatus::Pending && !reviewed {
+                pending.push(application);
+            }
+        }
+    }
+
+    Ok(pending)
+}
+
+pub fn get_my_applications() -> ZomeApiResult<Vec<ApplicationWithStatus>> {
+    let links = hdk::get_links(
+        &AGENT_ADDRESS,
+        LinkMatch::Exactly("applicant->badge_application"),
+        LinkMatch::Any,
+    )?;
+
+    links
+        .addresses()
+        .into_iter()
+        .map(get_application_with_status)
+        .collect()
+}
+
+/** Helpers */
+
+fn review_application(application_address: &Address, endorsed: bool) -> ZomeApiResult<Address> {
+    let review = ApplicationReview {
+        application: application_address.clone(),
+        reviewer: AGENT_ADDRESS.clone(),
+        endorsed,
+    };
+
+    let review_entry = Entry::App("application_review".into(), review.into());
+    let review_address = hdk::commit_entry(&review_entry)?;
+
+    hdk::link_entries(
+        application_address,
+        &review_address,
+        "badge_application->review",
+        "",
+    )?;
+
+    Ok(review_address)
+}
+
+/**
+ * Returns the application with its reviews: it is completed if the applicant already holds the badge
+ * under any version of the class, rejected if any issuer has rejected it, and pending otherwise
+ */
+fn get_application_with_status(address: Address) -> ZomeApiResult<ApplicationWithStatus> {
+    let application: BadgeApplication = hdk::utils::get_as_type(address.clone())?;
+
+    let reviews: Vec<ApplicationReview> = hdk::utils::get_links_and_load_type(
+        &address,
+        LinkMatch::Exactly("badge_application->review"),
+        LinkMatch::Any,
+    )?;
+
+    let (endorsements, rejections): (Vec<ApplicationReview>, Vec<ApplicationReview>) =
+        reviews.into_iter().partition(|review| review.endorsed);
+
+    let status =
+        if badge::assert_holder_valid(&application.badge_class, &application.applicant).is_ok() {
+            ApplicationStatus::Completed
+        } else if !rejections.is_empty() {
+            ApplicationStatus::Rejected
+        } else {
+            ApplicationStatus::Pending
+        };
+
+    Ok(ApplicationWithStatus {
+        address,
+        application,
+        endorsed_by: endorsements
+            .into_iter()
+            .map(|review| review.reviewer)
+            .collect(),
+        rejected_by: rejections
+            .into_iter()
+            .map(|review| review.reviewer)
+            .collect(),
+        status,
+    })
+}
@@ -0,0 +1,253 @@
+use crate::badge::{self, Badge};
+use crate::badge_class::BadgeClass;
+use crate::criteria;
+use crate::delegation;
+use hdk::holochain_core_types::time::Iso8601;
+use hdk::holochain_wasm_utils::api_serialization::get_links::GetLinksOptions;
+use hdk::prelude::*;
+use hdk::AGENT_ADDRESS;
+
+/**
+ * Statement of an issuer explaining why they claim that the recipient deserves a badge
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct Attestation {
+    pub badge: Address,
+    pub issuer: Address,
+    pub statement: String,
+    pub confidence: Confidence,
+    #[serde(default)]
+    pub criteria: Vec<String>,
+    /** Agent that signed the attestation on behalf of the issuer through a delegation, if any */
+    #[serde(default)]
+    pub delegate: Option<Address>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+/**
+ * Attestation together with the time at which the issuer made it
+ */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AttestationWithTimestamp {
+    pub address: Address,
+    pub attestation: Attestation,
+    pub issued_at: Option<Iso8601>,
+}
+
+/**
+ * Badge together with the attestations of all its issuers, identified by the address of its initial version
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct AttestedBadge {
+    pub address: Address,
+    pub badge: Badge,
+    pub attestations: Vec<AttestationWithTimestamp>,
+}
+
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "attestation",
+        description: "The statement of an issuer claiming that the recipient of a badge deserves it",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: |validation_data: hdk::EntryValidationData<Attestation>| {
+            match validation_data {
+                EntryValidationData::Create { entry, validation_data } => {
+                    let badge: Badge = hdk::utils::get_as_type(entry.badge.clone())?;
+
+                    match &entry.delegate {
+                        None => {
+                            if !validation_data.sources().contains(&entry.issuer) {
+                                return Err(String::from("Only the issuer can sign their attestation"));
+                            }
+                        },
+                        Some(delegate) => {
+                            if !validation_data.sources().contains(delegate) {
+                                return Err(String::from("Only the delegate can sign a delegated attestation"));
+                            }
+
+                            delegation::assert_delegated(&entry.issuer, delegate, &badge.badge_class, validation_data.package.chain_header.timestamp())?;
+                        }
+                    }
+
+                    if badge.recipient == entry.issuer {
+                        return Err(String::from("The recipient of a badge cannot attest for themselves"));
+                    }
+
+                    badge::assert_issuer_valid_at(&badge.badge_class, &entry.issuer, validation_data.package.chain_header.timestamp())?;
+
+                    let badge_class: BadgeClass = hdk::utils::get_as_type(badge.badge_class.clone())?;
+                    criteria::assert_criteria_exist(&badge_class, &entry.criteria)?;
+
+                    Ok(())
+                },
+                _ => Err(String::from("Cannot update or delete an attestation")),
+            }
+        },
+        links: [
+            from!(
+                "badge",
+                link_type: "badge->attestation",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, .. } => {
+                            let attestation: Attestation = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            match attestation.badge == link.link.base().clone() {
+                                true => Ok(()),
+                                false => Err(String::from("Cannot link \"badge->attestation\" to an attestation for another badge"))
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            ),
+            from!(
+                "%agent_id",
+                link_type: "issuer->attestation",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, .. } => {
+                            let attestation: Attestation = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            match attestation.issuer == link.link.base().clone() {
+                                true => Ok(()),
+                                false => Err(String::from("Cannot link \"issuer->attestation\" from an agent who is not the issuer of the attestation"))
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            )
+        ]
+    )
+}
+
+/** Handlers */
+
+/**
+ * Returns the latest version of the given badge with the attestations made for it, given any of its versions
+ */
+pub fn get_attested_badge(badge_address: &Address) -> ZomeApiResult<AttestedBadge> {
+    let badge: Badge = hdk::utils::get_as_type(badge_address.clone())?;
+    let initial_address = badge.initial_address()?;
+
+    Ok(AttestedBadge {
+        attestations: get_badge_attestations(&initial_address)?,
+        address: initial_address,
+        badge,
+    })
+}
+
+pub fn get_badge_attestations(badge_address: &Address) -> ZomeApiResult<Vec<AttestationWithTimestamp>> {
+    get_attestations(badge_address, "badge->attestation")
+}
+
+pub fn get_attestations_from_issuer(
+    agent_address: Address,
+) -> ZomeApiResult<Vec<AttestationWithTimestamp>> {
+    get_attestations(&agent_address, "issuer->attestation")
+}
+
+/** Helpers */
+
+/**
+ * Commits the attestation of the calling agent for the given badge,
+ * or of the given delegator if the calling agent is attesting on their behalf
+ */
+pub fn attest(
+    badge_address: &Address,
+    statement: String,
+    confidence: Confidence,
+    criteria: Vec<String>,
+    on_behalf_of: Option<Address>,
+) -> ZomeApiResult<Address> {
+    let attestation = Attestation {
+        badge: badge_address.clone(),
+        issuer: on_behalf_of.clone().unwrap_or(AGENT_ADDRESS.clone()),
+        statement,
+        confidence,
+        criteria,
+        delegate: on_behalf_of.map(|_| AGENT_ADDRESS.clone()),
+    };
+
+    let attestation_entry = Entry::App("attestation".into(), attestation.clone().into());
+    let attestation_address = hdk::commit_entry(&attestation_entry)?;
+
+    hdk::link_entries(badge_address, &attestation_address, "badge->attestation", "")?;
+    hdk::link_entries(
+        &attestation.issuer,
+        &attestation_address,
+        "issuer->attestation",
+        "",
+    )?;
+
+    Ok(attestation_address)
+}
+
+fn get_attestations(base: &Address, link_type: &str) -> ZomeApiResult<Vec<AttestationWithTimestamp>> {
+    let links = hdk::get_links_with_options(
+        base,
+        LinkMatch::Exactly(link_type),
+        LinkMatch::Any,
+        GetLinksOptions {
+            headers: true,
+            ..Default::default()
+        },
+    )?;
+
+    links
+        .links()
+        .into_iter()
+        .map(|link| {
+            let attestation: Attestation = hdk::utils::get_as_type(link.address.clone())?;
+
+            Ok(AttestationWithTimestamp {
+                address: link.address,
+                attestation,
+                issued_at: link.headers.first().map(|header| header.timestamp().clone()),
+            })
+        })
+        .collect()
+}
+
+/** Validation helpers */
+
+/**
+ * Asserts that the badge has an attestation from the given issuer, signed by the author of the claim
+ */
+pub fn assert_attested(
+    badge_address: &Address,
+    issuer: &Address,
+    author: &Address,
+) -> ZomeApiResult<()> {
+    let attested = get_badge_attestations(badge_address)?
+        .iter()
+        .any(|attestation| {
+            attestation.attestation.issuer == issuer.clone()
+                && attestation.attestation.delegate.as_ref().unwrap_or(issuer) == author
+        });
+
+    match attested {
+        true => Ok(()),
+        false => Err(ZomeApiError::from(format!(
+            "Issuer {} has not attested the badge",
+            issuer
+        ))),
+    }
+}
@@ -1,4 +1,16 @@
-use crate::badge_class::BadgeClass;
+use crate::attestation::{self, Confidence};
+use crate::badge_class::{self, BadgeClass, BadgeClassKind, IssuancePolicy};
+use crate::class_admins::{self, ClassAdmins};
+use crate::criteria;
+use crate::delegation;
+use crate::issuer_allowlist;
+use crate::lineage;
+use crate::notification::{self, NotificationMessage};
+use crate::organization::OrganizationRole;
+use crate::pathway;
+use crate::timestamp;
+use crate::tier_family;
+use hdk::holochain_core_types::time::Iso8601;
 use hdk::prelude::*;
 use hdk::AGENT_ADDRESS;
 
@@ -28,6 +40,13 @@ impl Badge {
 
         hdk::entry_address(&entry)
     }
+
+    /**
+     * Returns the address of the initial version of this badge, which identifies it across its updates
+     */
+    pub fn initial_address(&self) -> ZomeApiResult<Address> {
+        Badge::initial(&self.recipient, &self.badge_class).address()
+    }
 }
 
 pub fn entry_def() -> ValidatingEntryType {
@@ -40,7 +59,7 @@ pub fn entry_def() -> ValidatingEntryType {
         },
         validation: |validation_data: hdk::EntryValidationData<Badge>| {
             match validation_data {
-                EntryValidationData::Create { entry, .. } => {
+                EntryValidationData::Create { entry, validation_data } => {
                     if entry.issuers.len() > 0 {
                         return Err(String::from("No issuers can be present when creating a Badge"));
                     }
@@ -49,6 +68,17 @@ pub fn entry_def() -> ValidatingEntryType {
                         return Err(String::from("No evidences can be present when creating a Badge"));
                     }
 
+                    let badge_class: BadgeClass = hdk::utils::get_as_type(entry.badge_class.clone())?;
+
+                    if let BadgeClassKind::Pathway { components } = &badge_class.kind {
+                        if !validation_data.sources().contains(&entry.recipient) {
+                            return Err(String::from("Only the recipient of a pathway badge can earn it"));
+                        }
+
+                        pathway::assert_components_held(components, &entry.recipient)?;
+                        assert_recipient_accepted(&entry.badge_class, &badge_class, &entry.recipient, validation_data.package.chain_header.timestamp())?;
+                    }
+
                     Ok(())
                 },
                 EntryValidationData::Modify {
@@ -70,12 +100,26 @@ pub fn entry_def() -> ValidatingEntryType {
                     }
 
                     let author = &validation_data.sources()[0];
+                    let claimed_at = validation_data.package.chain_header.timestamp();
 
                     // TODO validate evidences
-                    if get_new_issuer(&new_entry.issuers, &old_entry.issuers)? != author.clone() {
-                        return Err(String::from("The issuer of a badge can only add themselves to the issuers list"));
+                    let new_issuer = get_new_issuer(&new_entry.issuers, &old_entry.issuers)?;
+
+                    // The issuer of a badge can only add themselves, or an issuer that delegated to them, to the issuers list
+                    if new_issuer != author.clone() {
+                        delegation::assert_delegated(&new_issuer, author, &new_entry.badge_class, claimed_at)?;
+                    }
+                    attestation::assert_attested(&new_entry.initial_address()?, &new_issuer, author)?;
+
+                    let badge_class: BadgeClass = hdk::utils::get_as_type(new_entry.badge_class.clone())?;
+
+                    if let BadgeClassKind::Pathway { .. } = badge_class.kind {
+                        return Err(String::from("Pathway badges cannot be claimed by issuers"));
                     }
-                    assert_issuer_valid(&new_entry.badge_class, &author)?;
+
+                    assert_issuer_valid_at(&new_entry.badge_class, &new_issuer, claimed_at)?;
+                    assert_recipient_accepted(&new_entry.badge_class, &badge_class, &new_entry.recipient, claimed_at)?;
+
                     Ok(())
                 },
                 _ => Err(String::from("Cannot update or delete a badge class")),
@@ -129,11 +173,33 @@ pub fn entry_def() -> ValidatingEntryType {
 
 /** Handlers */
 
+/**
+ * Claims that the recipient deserves the badge, as the calling agent or on behalf of
+ * an issuer that has delegated the badge class to it.
+ * Claims are always made against the latest version of the badge class
+ */
 pub fn claim_agent_deserves_badge(
     recipient: Address,
     badge_class: Address,
     evidences: Vec<Address>,
+    statement: String,
+    confidence: Confidence,
+    criteria: Vec<String>,
+    on_behalf_of: Option<Address>,
 ) -> ZomeApiResult<Address> {
+    let badge_class = badge_class::get_latest_version(&badge_class)?;
+
+    if let Some(delegator) = &on_behalf_of {
+        delegation::assert_delegated(
+            delegator,
+            &AGENT_ADDRESS,
+            &badge_class,
+            &timestamp::latest_chain_time()?,
+        )?;
+    }
+
+    let issuer = on_behalf_of.clone().unwrap_or(AGENT_ADDRESS.clone());
+
     let mut badge = Badge::initial(&recipient, &badge_class);
 
     let initial_entry = Entry::App("badge".into(), badge.clone().into());
@@ -142,59 +208,273 @@ pub fn claim_agent_deserves_badge(
 
     let last_entry: Option<Entry> = hdk::get_entry(&badge_address)?;
 
-    if let Some(_) = last_entry {
-        badge = hdk::utils::get_as_type(badge_address.clone())?;
-    } else {
-        hdk::commit_entry(&initial_entry)?;
-    }
+    let was_completed = match last_entry {
+        Some(_) => {
+            badge = hdk::utils::get_as_type(badge_address.clone())?;
+            assert_badge_completed(&badge.badge_class, &badge.recipient).is_ok()
+        }
+        None => {
+            hdk::commit_entry(&initial_entry)?;
+            false
+        }
+    };
+
+    attestation::attest(
+        &badge_address,
+        statement,
+        confidence,
+        criteria,
+        on_behalf_of,
+    )?;
 
-    badge.issuers.push(AGENT_ADDRESS.clone());
+    badge.issuers.push(issuer.clone());
     badge.evidences.append(&mut evidences.clone());
 
     let new_entry = Entry::App("badge".into(), badge.clone().into());
     let address = hdk::update_entry(new_entry, &badge_address)?;
 
     hdk::link_entries(
-        &AGENT_ADDRESS,
+        &issuer,
         &badge_address,
         "issuer->badge",
         String::from(badge_class.clone()).as_str(),
     )?;
 
-    let tag = match assert_issuer_valid(&badge.badge_class, &badge.recipient) {
+    let tag = match assert_badge_completed(&badge.badge_class, &badge.recipient) {
         Ok(()) => "completed",
         Err(_) => "temptative",
     };
 
     hdk::link_entries(&recipient, &badge_address, "recipient->badge", tag)?;
     hdk::link_entries(&badge_class, &address, "badge_class->badge", "")?;
+
+    notification::notify(
+        &recipient,
+        NotificationMessage::BadgeClaimed {
+            badge: badge_address.clone(),
+            badge_class: badge_class.clone(),
+            issuer: issuer.clone(),
+        },
+    )?;
+    notification::notify(
+        &class_admins::get_class_admins(&badge_class)?.owner,
+        NotificationMessage::ClassClaimed {
+            badge: badge_address.clone(),
+            badge_class: badge_class.clone(),
+            recipient: recipient.clone(),
+            issuer,
+        },
+    )?;
+
+    if tag == "completed" && !was_completed {
+        notification::notify(
+            &recipient,
+            NotificationMessage::BadgeCompleted {
+                badge: badge_address.clone(),
+                badge_class,
+            },
+        )?;
+    }
+
     Ok(badge_address)
 }
 
 /** Validation helpers */
 
 /**
- * Badge claims are valid if there are more actual claims than validators,
- * or if one of the claims comes from the badge creator itself
+ * Asserts that the recipient can get a badge of the class at the given time:
+ * they must hold the previous tier of the class, the issuance window must be open
+ * and the class must not have reached its maximum of holders
  */
-fn assert_issuer_valid(badge_class_address: &Address, issuer: &Address) -> ZomeApiResult<()> {
+fn assert_recipient_accepted(
+    badge_class_address: &Address,
+    badge_class: &BadgeClass,
+    recipient: &Address,
+    timestamp: &Iso8601,
+) -> ZomeApiResult<()> {
+    tier_family::assert_lower_tier_held(badge_class_address, recipient)?;
+    badge_class::assert_issuance_open(badge_class, timestamp)?;
+
+    if let Some(max_holders) = badge_class.max_holders {
+        let holders = get_completed_holders(badge_class_address)?;
+
+        if !holders.contains(recipient) && holders.len() >= max_holders {
+            return Err(ZomeApiError::from(format!(
+                "Badge {} has already reached its maximum of {} holders",
+                badge_class.name, max_holders
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/**
+ * Issuers are valid depending on the issuance policy of the badge class:
+ * - holders: if they hold a completed badge for any version of the badge class
+ * - allowlist: if they are in the issuer allowlist of the badge class
+ * - creator-only: if they are the owner or an administrator of the badge class
+ *
+ * In every policy, the members with the issuer role of an organization owning or administering the class are valid
+ */
+pub fn assert_issuer_valid(badge_class_address: &Address, issuer: &Address) -> ZomeApiResult<()> {
+    let admins = class_admins::get_class_admins(badge_class_address)?;
+
+    assert_issuer_valid_with_admins(badge_class_address, issuer, &admins)
+}
+
+/**
+ * Asserts that the issuer was valid at the given time, with the administrators the badge class had then
+ */
+pub fn assert_issuer_valid_at(
+    badge_class_address: &Address,
+    issuer: &Address,
+    at: &Iso8601,
+) -> ZomeApiResult<()> {
+    let admins = class_admins::get_class_admins_at(badge_class_address, at)?;
+
+    assert_issuer_valid_with_admins(badge_class_address, issuer, &admins)
+}
+
+fn assert_issuer_valid_with_admins(
+    badge_class_address: &Address,
+    issuer: &Address,
+    admins: &ClassAdmins,
+) -> ZomeApiResult<()> {
     let badge_class: BadgeClass = hdk::utils::get_as_type(badge_class_address.clone())?;
+    let is_class_issuer = admins.acts_for(issuer, OrganizationRole::Issuer)?;
 
-    if badge_class.creator_address == issuer.clone() {
-        return Ok(());
+    match badge_class.issuance_policy {
+        IssuancePolicy::Holders => {
+            if is_class_issuer {
+                return Ok(());
+            }
+
+            assert_holder_valid(badge_class_address, issuer)
+        }
+        IssuancePolicy::Allowlist => {
+            if is_class_issuer
+                || issuer_allowlist::get_allowed_issuers(badge_class_address)?.contains(issuer)
+            {
+                return Ok(());
+            }
+
+            Err(ZomeApiError::from(format!(
+                "Issuer {} is not in the allowlist of badge {}",
+                issuer, badge_class.name
+            )))
+        }
+        IssuancePolicy::CreatorOnly => match is_class_issuer {
+            true => Ok(()),
+            false => Err(ZomeApiError::from(format!(
+                "Only the owner or administrators of badge {} can issue it",
+                badge_class.name
+            ))),
+        },
     }
+}
+
+/**
+ * Holders are valid issuers if they hold a completed badge for any version of the badge class
+ */
+pub fn assert_holder_valid(badge_class_address: &Address, issuer: &Address) -> ZomeApiResult<()> {
+    let result = assert_badge_completed(badge_class_address, issuer);
+    if result.is_ok() {
+        return result;
+    }
+
+    for version in badge_class::get_class_versions(badge_class_address)? {
+        if version != badge_class_address.clone()
+            && assert_badge_completed(&version, issuer).is_ok()
+        {
+            return Ok(());
+        }
+    }
+
+    result
+}
+
+/**
+ * Badges are completed if there are as many actual claims as validators.
+ * If the class requires a minimum of lineages, the claims must also come from that many independent lineages.
+ *
+ * Being the owner or an administrator of the class makes an agent a valid issuer, but does not complete their own badge
+ */
+pub fn assert_badge_completed(
+    badge_class_address: &Address,
+    issuer: &Address,
+) -> ZomeApiResult<()> {
+    let badge_class: BadgeClass = hdk::utils::get_as_type(badge_class_address.clone())?;
 
     let badge = Badge::initial(&issuer, &badge_class_address);
 
     let latest_badge: Badge = hdk::utils::get_as_type(badge.address()?)?;
 
-    match latest_badge.issuers.len() >= badge_class.validators {
-        true => Ok(()),
-        false => Err(ZomeApiError::from(format!(
+    assert_claims_sufficient(badge_class_address, &badge_class, &latest_badge)
+}
+
+/**
+ * Asserts that the claims of the given badge reach the validators, lineages and required criteria of its class.
+ * Pathway badges do not need claims, since their components were checked when they were earned
+ */
+fn assert_claims_sufficient(
+    badge_class_address: &Address,
+    badge_class: &BadgeClass,
+    badge: &Badge,
+) -> ZomeApiResult<()> {
+    if let BadgeClassKind::Pathway { .. } = badge_class.kind {
+        return Ok(());
+    }
+
+    if badge.issuers.len() < badge_class.validators {
+        return Err(ZomeApiError::from(format!(
             "Issuer {} for badge {} is not valid",
-            issuer, badge_class.name
-        ))),
+            badge.recipient, badge_class.name
+        )));
+    }
+
+    if let Some(min_lineages) = badge_class.min_lineages {
+        let lineages = lineage::count_independent_lineages(badge_class_address, &badge.issuers)?;
+
+        if lineages < min_lineages {
+            return Err(ZomeApiError::from(format!(
+                "Issuer {} for badge {} only has claims from {} independent lineages, {} are required",
+                badge.recipient, badge_class.name, lineages, min_lineages
+            )));
+        }
     }
+
+    criteria::assert_required_criteria_covered(badge_class, badge)
+}
+
+/**
+ * Returns the recipients that have completed a badge of any version of the given class,
+ * each badge checked against the version it was claimed for
+ */
+pub fn get_completed_holders(badge_class_address: &Address) -> ZomeApiResult<Vec<Address>> {
+    let mut holders: Vec<Address> = vec![];
+
+    for version in badge_class::get_class_versions(badge_class_address)? {
+        let badge_class: BadgeClass = hdk::utils::get_as_type(version.clone())?;
+
+        let links = hdk::get_links(
+            &version,
+            LinkMatch::Exactly("badge_class->badge"),
+            LinkMatch::Any,
+        )?;
+
+        for address in links.addresses() {
+            let badge: Badge = hdk::utils::get_as_type(address)?;
+
+            if !holders.contains(&badge.recipient)
+                && assert_claims_sufficient(&version, &badge_class, &badge).is_ok()
+            {
+                holders.push(badge.recipient);
+            }
+        }
+    }
+
+    Ok(holders)
 }
 
 fn get_new_issuer(
@@ -1,6 +1,18 @@
+use hdk::holochain_core_types::time::Iso8601;
 use hdk::prelude::*;
+use hdk::AGENT_ADDRESS;
 
-use crate::badge::Badge;
+use crate::alignment::{self, Alignment};
+use crate::anchor;
+use crate::badge::{self, Badge};
+use crate::class_admins;
+use crate::criteria::{self, Criterion};
+use crate::issuer_allowlist;
+use crate::organization::{self, OrganizationRole};
+use crate::search;
+use crate::tag;
+use crate::timestamp;
+use crate::visibility;
 
 #[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
 pub struct BadgeClass {
@@ -9,6 +21,73 @@ pub struct BadgeClass {
     pub creator_address: Address,
     pub image: String,
     pub validators: usize,
+    #[serde(default)]
+    pub min_lineages: Option<usize>,
+    #[serde(default)]
+    pub supersedes: Option<Address>,
+    #[serde(default)]
+    pub issuance_policy: IssuancePolicy,
+    #[serde(default)]
+    pub max_holders: Option<usize>,
+    #[serde(default)]
+    pub issuance_opens_at: Option<Iso8601>,
+    #[serde(default)]
+    pub issuance_closes_at: Option<Iso8601>,
+    #[serde(default)]
+    pub kind: BadgeClassKind,
+    #[serde(default)]
+    pub criteria: Vec<Criterion>,
+    #[serde(default)]
+    pub alignments: Vec<Alignment>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/**
+ * How the badges of a class are earned:
+ * - claims: by gathering claims from valid issuers
+ * - pathway: by holding completed badges of all the component classes
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum BadgeClassKind {
+    Claims,
+    Pathway { components: Vec<Address> },
+}
+
+impl Default for BadgeClassKind {
+    fn default() -> Self {
+        BadgeClassKind::Claims
+    }
+}
+
+/**
+ * Badges of all the versions of a class, with the number of holders the class still accepts if it is limited
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct ClassBadges {
+    pub badges: Vec<Address>,
+    pub remaining_capacity: Option<usize>,
+}
+
+/**
+ * Who can issue the badges of a class:
+ * - holders: anyone holding the badge, besides the owner and administrators of the class
+ * - allowlist: only the agents explicitly allowed by the owner or administrators of the class
+ * - creator-only: only the owner and administrators of the class
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum IssuancePolicy {
+    Holders,
+    Allowlist,
+    CreatorOnly,
+}
+
+impl Default for IssuancePolicy {
+    fn default() -> Self {
+        IssuancePolicy::Holders
+    }
 }
 
 pub fn entry_def() -> ValidatingEntryType {
@@ -21,7 +100,49 @@ pub fn entry_def() -> ValidatingEntryType {
         },
         validation: |validation_data: hdk::EntryValidationData<BadgeClass>| {
             match validation_data {
-                EntryValidationData::Create { .. } => {
+                EntryValidationData::Create { entry, validation_data } => {
+                    if entry.min_lineages == Some(0) {
+                        return Err(String::from("The minimum number of lineages of a badge class must be greater than 0"));
+                    }
+
+                    if entry.max_holders == Some(0) {
+                        return Err(String::from("The maximum number of holders of a badge class must be greater than 0"));
+                    }
+
+                    if let (Some(opens_at), Some(closes_at)) = (&entry.issuance_opens_at, &entry.issuance_closes_at) {
+                        if timestamp::seconds(opens_at)? >= timestamp::seconds(closes_at)? {
+                            return Err(String::from("The issuance window of a badge class must open before it closes"));
+                        }
+                    }
+
+                    criteria::assert_criteria_valid(&entry.criteria)?;
+                    alignment::assert_alignments_valid(&entry.alignments)?;
+                    tag::assert_tags_valid(&entry.tags)?;
+
+                    if let BadgeClassKind::Pathway { components } = &entry.kind {
+                        if components.is_empty() {
+                            return Err(String::from("A pathway badge class must have at least one component"));
+                        }
+
+                        for component in components {
+                            hdk::utils::get_as_type::<BadgeClass>(component.clone())?;
+                        }
+                    }
+
+                    if let Some(previous_version) = entry.supersedes {
+                        let previous_admins = class_admins::get_class_admins(&previous_version)?;
+
+                        if previous_admins.owner != entry.creator_address {
+                            return Err(String::from("Only the owner of a badge class can create a new version of it"));
+                        }
+                    }
+
+                    if let Some(organization) = organization::get_organization(&entry.creator_address)? {
+                        if !validation_data.sources().iter().any(|source| organization.has_role(source, &OrganizationRole::Admin)) {
+                            return Err(String::from("Only the admins of an organization can create badge classes on its behalf"));
+                        }
+                    }
+
                 /*  let creator_address = entry.creator_address;
 
                     if !validation_data.clone().sources().contains(&creator_address) {
@@ -70,9 +191,171 @@ pub fn entry_def() -> ValidatingEntryType {
                         hdk::LinkValidationData::LinkAdd { link, .. } => {
                             let badge_class: BadgeClass = hdk::utils::get_as_type(link.link.target().clone())?;
 
-                            match badge_class.creator_address == link.link.base().clone() {
+                            if badge_class.creator_address == link.link.base().clone() {
+                                return Ok(());
+                            }
+
+                            match class_admins::get_class_owner(link.link.target())? == link.link.base().clone() {
                                 true => Ok(()),
-                                false => Err(String::from("Cannot link \"creator->badge_class\" to a badge class that was not created by the base address"))
+                                false => Err(String::from("Cannot link \"creator->badge_class\" to a badge class that is not owned by the base address"))
+                            }
+                        },
+                        hdk::LinkValidationData::LinkRemove { link, validation_data } => {
+                            class_admins::assert_owner_link_removable(link.link.base(), link.link.target(), &validation_data.sources())?;
+
+                            Ok(())
+                        }
+                    }
+                }
+            ),
+            from!(
+                anchor::LEGACY_ANCHOR_ENTRY_TYPE,
+                link_type: "anchor->badge_class",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, validation_data } => {
+                            if link.link.base().clone() != anchor::legacy_root_address()? {
+                                return Err(String::from("Can only link \"anchor->badge_class\" from the legacy root anchor"));
+                            }
+
+                            let badge_class: BadgeClass = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            match organization::signed_by(&badge_class.creator_address, &validation_data.sources(), OrganizationRole::Admin)? {
+                                true => Ok(()),
+                                false => Err(String::from("Only the creator of a badge class can link it from the legacy root anchor"))
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            ),
+            from!(
+                anchor::ANCHOR_ENTRY_TYPE,
+                link_type: "shard->badge_class",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, .. } => {
+                            let shard = anchors::get_anchor_of_type(link.link.base(), anchor::SHARD_ANCHOR_TYPE)?;
+
+                            match shard.anchor_text == Some(anchor::shard(link.link.target())) {
+                                true => Ok(()),
+                                false => Err(String::from("Cannot link \"shard->badge_class\" to a badge class from another shard"))
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            ),
+            from!(
+                anchor::ANCHOR_ENTRY_TYPE,
+                link_type: "tag->badge_class",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, validation_data } => {
+                            let tag = anchors::get_anchor_of_type(link.link.base(), anchor::TAG_ANCHOR_TYPE)?;
+                            let badge_class: BadgeClass = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            if !badge_class.tags.iter().any(|class_tag| Some(class_tag.clone()) == tag.anchor_text) {
+                                return Err(String::from("Cannot link \"tag->badge_class\" to a badge class that is not tagged with the anchor tag"));
+                            }
+
+                            match organization::signed_by(&badge_class.creator_address, &validation_data.sources(), OrganizationRole::Admin)? {
+                                true => Ok(()),
+                                false => Err(String::from("Only the creator of a badge class can tag it"))
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            ),
+            from!(
+                anchor::ANCHOR_ENTRY_TYPE,
+                link_type: "search->badge_class",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, validation_data } => {
+                            let token = anchors::get_anchor_of_type(link.link.base(), anchor::SEARCH_ANCHOR_TYPE)?;
+                            let badge_class: BadgeClass = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            if !search::badge_class_tokens(&badge_class).iter().any(|class_token| Some(class_token.clone()) == token.anchor_text) {
+                                return Err(String::from("Cannot link \"search->badge_class\" to a badge class that does not contain the anchor token"));
+                            }
+
+                            match organization::signed_by(&badge_class.creator_address, &validation_data.sources(), OrganizationRole::Admin)? {
+                                true => Ok(()),
+                                false => Err(String::from("Only the creator of a badge class can index it"))
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            ),
+            from!(
+                anchor::ANCHOR_ENTRY_TYPE,
+                link_type: "alignment->badge_class",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, .. } => {
+                            let competency = anchors::get_anchor_of_type(link.link.base(), anchor::ALIGNMENT_ANCHOR_TYPE)?;
+                            let badge_class: BadgeClass = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            let aligned = badge_class.alignments.iter().any(|alignment| {
+                                match (&alignment.target_framework, &alignment.target_code) {
+                                    (Some(framework), Some(code)) => Some(anchor::alignment_text(framework, code)) == competency.anchor_text,
+                                    _ => false,
+                                }
+                            });
+
+                            match aligned {
+                                true => Ok(()),
+                                false => Err(String::from("Cannot link \"alignment->badge_class\" to a badge class that is not aligned to the anchor competency"))
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            ),
+            to!(
+                "badge_class",
+                link_type: "badge_class->next_version",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, validation_data } => {
+                            let previous_admins = class_admins::get_class_admins(link.link.base())?;
+                            let next_class: BadgeClass = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            if next_class.supersedes != Some(link.link.base().clone()) {
+                                return Err(String::from("Cannot link \"badge_class->next_version\" to a badge class that does not supersede the base class"));
+                            }
+
+                            // Versions form a single chain: a class can only be superseded once
+                            if let Some(next_version) = get_next_version(link.link.base())? {
+                                if &next_version != link.link.target() {
+                                    return Err(String::from("Cannot link a second \"badge_class->next_version\" from a badge class that has already been superseded"));
+                                }
+                            }
+
+                            match organization::signed_by(&previous_admins.owner, &validation_data.sources(), OrganizationRole::Admin)? {
+                                true => Ok(()),
+                                false => Err(String::from("Only the owner of a badge class can link a new version of it"))
                             }
                         },
                         _ => Err(String::from("Cannot delete links"))
@@ -82,3 +365,202 @@ pub fn entry_def() -> ValidatingEntryType {
         ]
     )
 }
+
+/** Handlers */
+
+pub fn create_badge_class(class: BadgeClass) -> ZomeApiResult<Address> {
+    if !organization::acts_for(
+        &class.creator_address,
+        &AGENT_ADDRESS,
+        OrganizationRole::Admin,
+    )? {
+        return Err(ZomeApiError::from(String::from(
+            "Only the admins of an organization can create badge classes on its behalf",
+        )));
+    }
+
+    let class_entry = Entry::App("badge_class".into(), class.clone().into());
+    let class_address = hdk::commit_entry(&class_entry)?;
+
+    alignment::index_alignments(&class_address, &class)?;
+    tag::index_tags(&class_address, &class)?;
+    search::index_badge_class(&class_address, &class)?;
+
+    anchor::link_badge_class(&class_address)?;
+
+    let creator_link_type = match class.creator_address == AGENT_ADDRESS.clone() {
+        true => "creator->badge_class",
+        false => "organization->badge_class",
+    };
+
+    hdk::link_entries(
+        &class.creator_address,
+        &class_address,
+        creator_link_type,
+        "",
+    )?;
+
+    Ok(class_address)
+}
+
+/**
+ * Returns the badges of all the versions of the class that the calling agent is allowed to see,
+ * with the number of holders the latest version still accepts
+ */
+pub fn get_badges_for_class(badge_class: Address) -> ZomeApiResult<ClassBadges> {
+    let mut badges: Vec<Address> = vec![];
+
+    for version in get_class_versions(&badge_class)? {
+        let links = hdk::get_links(
+            &version,
+            LinkMatch::Exactly("badge_class->badge"),
+            LinkMatch::Any,
+        )?;
+
+        badges.append(&mut links.addresses());
+    }
+
+    Ok(ClassBadges {
+        badges: visibility::filter_visible_badges(badges)?,
+        remaining_capacity: get_remaining_capacity(&badge_class)?,
+    })
+}
+
+pub fn create_badge_class_version(
+    previous_version: Address,
+    mut class: BadgeClass,
+) -> ZomeApiResult<Address> {
+    let latest_version = get_latest_version(&previous_version)?;
+    if latest_version != previous_version {
+        return Err(ZomeApiError::from(format!(
+            "Badge class {} has already been superseded by {}",
+            previous_version, latest_version
+        )));
+    }
+
+    let previous_admins = class_admins::get_class_admins(&previous_version)?;
+    if !organization::acts_for(
+        &previous_admins.owner,
+        &AGENT_ADDRESS,
+        OrganizationRole::Admin,
+    )? {
+        return Err(ZomeApiError::from(String::from(
+            "Only the owner of a badge class can create a new version of it",
+        )));
+    }
+
+    class.creator_address = previous_admins.owner;
+    class.supersedes = Some(previous_version.clone());
+
+    let class_entry = Entry::App("badge_class".into(), class.clone().into());
+    let class_address = hdk::commit_entry(&class_entry)?;
+
+    alignment::index_alignments(&class_address, &class)?;
+    tag::index_tags(&class_address, &class)?;
+    search::index_badge_class(&class_address, &class)?;
+
+    hdk::link_entries(
+        &previous_version,
+        &class_address,
+        "badge_class->next_version",
+        "",
+    )?;
+
+    class_admins::carry_over_class_admins(&previous_version, &class_address)?;
+    issuer_allowlist::carry_over_allowed_issuers(&previous_version, &class_address)?;
+
+    Ok(class_address)
+}
+
+/** Helpers */
+
+/**
+ * Returns the number of holders the latest version of the class still accepts, counting the holders
+ * of all its versions, or None if it is not limited
+ */
+fn get_remaining_capacity(badge_class: &Address) -> ZomeApiResult<Option<usize>> {
+    let latest_version = get_latest_version(badge_class)?;
+    let class: BadgeClass = hdk::utils::get_as_type(latest_version.clone())?;
+
+    match class.max_holders {
+        Some(max_holders) => {
+            let holders = badge::get_completed_holders(&latest_version)?;
+            Ok(Some(max_holders.saturating_sub(holders.len())))
+        }
+        None => Ok(None),
+    }
+}
+
+/**
+ * Returns the version that directly supersedes the given badge class, if any
+ */
+pub fn get_next_version(badge_class: &Address) -> ZomeApiResult<Option<Address>> {
+    let links = hdk::get_links(
+        badge_class,
+        LinkMatch::Exactly("badge_class->next_version"),
+        LinkMatch::Any,
+    )?;
+
+    Ok(links.addresses().first().cloned())
+}
+
+/**
+ * Returns the latest version of the given badge class
+ */
+pub fn get_latest_version(badge_class: &Address) -> ZomeApiResult<Address> {
+    let mut latest_version = badge_class.clone();
+
+    while let Some(next_version) = get_next_version(&latest_version)? {
+        latest_version = next_version;
+    }
+
+    Ok(latest_version)
+}
+
+/**
+ * Returns all the versions of the family of the given badge class, from the oldest to the latest
+ */
+pub fn get_class_versions(badge_class: &Address) -> ZomeApiResult<Vec<Address>> {
+    let mut versions: Vec<Address> = vec![badge_class.clone()];
+
+    let mut class: BadgeClass = hdk::utils::get_as_type(badge_class.clone())?;
+    while let Some(previous_version) = class.supersedes {
+        versions.insert(0, previous_version.clone());
+        class = hdk::utils::get_as_type(previous_version)?;
+    }
+
+    let mut next_version = get_next_version(badge_class)?;
+    while let Some(version) = next_version {
+        next_version = get_next_version(&version)?;
+        versions.push(version);
+    }
+
+    Ok(versions)
+}
+
+/**
+ * Asserts that the badge class accepts claims at the given time
+ */
+pub fn assert_issuance_open(badge_class: &BadgeClass, at: &Iso8601) -> ZomeApiResult<()> {
+    let at_seconds = timestamp::seconds(at)?;
+
+    if let Some(opens_at) = &badge_class.issuance_opens_at {
+        if at_seconds < timestamp::seconds(opens_at)? {
+            return Err(ZomeApiError::from(format!(
+                "Issuance of badge {} opens at {}",
+                badge_class.name, opens_at
+            )));
+        }
+    }
+
+    if let Some(closes_at) = &badge_class.issuance_closes_at {
+        if at_seconds > timestamp::seconds(closes_at)? {
+            return Err(ZomeApiError::from(format!(
+                "Issuance of badge {} closed at {}",
+                badge_class.name, closes_at
+            )));
+        }
+    }
+
+    Ok(())
+}
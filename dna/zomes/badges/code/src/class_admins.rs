@@ -0,0 +1,357 @@
+use crate::badge_class::{self, BadgeClass};
+use crate::organization::{self, OrganizationRole};
+use crate::timestamp;
+use hdk::holochain_core_types::time::Iso8601;
+use hdk::holochain_wasm_utils::api_serialization::get_entry::{
+    GetEntryOptions, GetEntryResultType, StatusRequestKind,
+};
+use hdk::prelude::*;
+use hdk::AGENT_ADDRESS;
+use std::convert::TryFrom;
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct ClassAdmins {
+    pub badge_class: Address,
+    pub owner: Address,
+    pub admins: Vec<Address>,
+}
+
+impl ClassAdmins {
+    /**
+     * Returns the initial administrators of the given class, with its creator as the only owner
+     */
+    pub fn initial(badge_class: &Address, creator_address: &Address) -> ClassAdmins {
+        ClassAdmins {
+            badge_class: badge_class.clone(),
+            owner: creator_address.clone(),
+            admins: vec![],
+        }
+    }
+
+    pub fn address(&self) -> ZomeApiResult<Address> {
+        let entry = Entry::App("class_admins".into(), self.clone().into());
+
+        hdk::entry_address(&entry)
+    }
+
+    pub fn contains(&self, agent_address: &Address) -> bool {
+        self.owner == agent_address.clone() || self.admins.contains(agent_address)
+    }
+
+    /**
+     * Returns whether the agent is the owner or an administrator, or has the given role
+     * in an organization that is the owner or an administrator
+     */
+    pub fn acts_for(&self, agent_address: &Address, role: OrganizationRole) -> ZomeApiResult<bool> {
+        if self.contains(agent_address) {
+            return Ok(true);
+        }
+
+        for principal in std::iter::once(&self.owner).chain(self.admins.iter()) {
+            if organization::acts_for(principal, agent_address, role.clone())? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "class_admins",
+        description: "The owner and co-administrators of a badge class",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: |validation_data: hdk::EntryValidationData<ClassAdmins>| {
+            match validation_data {
+                EntryValidationData::Create { entry, .. } => {
+                    let badge_class: BadgeClass = hdk::utils::get_as_type(entry.badge_class.clone())?;
+
+                    if entry.owner != badge_class.creator_address {
+                        return Err(String::from("The initial owner of a badge class must be its creator"));
+                    }
+
+                    if entry.admins.len() > 0 {
+                        return Err(String::from("No administrators can be present when creating the administrators of a badge class"));
+                    }
+
+                    Ok(())
+                },
+                EntryValidationData::Modify {
+                    new_entry,
+                    old_entry,
+                    validation_data,
+                    ..
+                } => {
+                    if new_entry.badge_class != old_entry.badge_class {
+                        return Err(String::from("Cannot change the class of its administrators"));
+                    }
+
+                    let author = &validation_data.sources()[0];
+
+                    if !old_entry.acts_for(author, OrganizationRole::Admin)? {
+                        return Err(String::from("Only the owner or an administrator of a badge class can change its administrators"));
+                    }
+
+                    if new_entry.owner != old_entry.owner && !organization::acts_for(&old_entry.owner, author, OrganizationRole::Admin)? {
+                        return Err(String::from("Only the owner of a badge class can transfer its ownership"));
+                    }
+
+                    if new_entry.admins.contains(&new_entry.owner) {
+                        return Err(String::from("The owner of a badge class cannot also be one of its administrators"));
+                    }
+
+                    Ok(())
+                },
+                _ => Err(String::from("Cannot delete the administrators of a badge class")),
+            }
+        },
+        links: []
+    )
+}
+
+/** Handlers */
+
+pub fn add_class_admin(badge_class: Address, admin: Address) -> ZomeApiResult<Address> {
+    update_class_admins(&badge_class, |class_admins| {
+        if class_admins.contains(&admin) {
+            return Err(ZomeApiError::from(format!(
+                "Agent {} is already an administrator of the badge class",
+                admin
+            )));
+        }
+
+        class_admins.admins.push(admin.clone());
+        Ok(())
+    })
+}
+
+pub fn remove_class_admin(badge_class: Address, admin: Address) -> ZomeApiResult<Address> {
+    update_class_admins(&badge_class, |class_admins| {
+        if !class_admins.admins.contains(&admin) {
+            return Err(ZomeApiError::from(format!(
+                "Agent {} is not an administrator of the badge class",
+                admin
+            )));
+        }
+
+        class_admins.admins.retain(|a| a.clone() != admin);
+        Ok(())
+    })
+}
+
+/**
+ * Transfers the latest version of the badge class to the new owner,
+ * moving the link from its owner that lists it among their created classes
+ */
+pub fn transfer_class_ownership(
+    badge_class: Address,
+    new_owner: Address,
+) -> ZomeApiResult<Address> {
+    let badge_class = badge_class::get_latest_version(&badge_class)?;
+    let previous_owner = get_class_admins(&badge_class)?.owner;
+
+    let address = update_class_admins(&badge_class, |class_admins| {
+        if !organization::acts_for(&class_admins.owner, &AGENT_ADDRESS, OrganizationRole::Admin)? {
+            return Err(ZomeApiError::from(String::from(
+                "Only the owner of a badge class can transfer its ownership",
+            )));
+        }
+
+        class_admins.admins.retain(|a| a.clone() != new_owner);
+        class_admins.owner = new_owner.clone();
+        Ok(())
+    })?;
+
+    // Created classes are listed from the first version of each class
+    let first_version = badge_class::get_class_versions(&badge_class)?[0].clone();
+
+    hdk::remove_link(
+        &previous_owner,
+        &first_version,
+        owner_link_type(&previous_owner)?,
+        "",
+    )?;
+    hdk::link_entries(&new_owner, &first_version, owner_link_type(&new_owner)?, "")?;
+
+    Ok(address)
+}
+
+/** Helpers */
+
+/**
+ * Returns the current administrators of the given badge class
+ */
+pub fn get_class_admins(badge_class: &Address) -> ZomeApiResult<ClassAdmins> {
+    let class: BadgeClass = hdk::utils::get_as_type(badge_class.clone())?;
+    let initial = ClassAdmins::initial(badge_class, &class.creator_address);
+    let initial_address = initial.address()?;
+
+    match hdk::get_entry(&initial_address)? {
+        Some(_) => hdk::utils::get_as_type(initial_address),
+        None => Ok(initial),
+    }
+}
+
+/**
+ * Returns the administrators of the given badge class as they were at the given time,
+ * so that validating a claim does not depend on the changes made to them afterwards
+ */
+pub fn get_class_admins_at(badge_class: &Address, at: &Iso8601) -> ZomeApiResult<ClassAdmins> {
+    let class: BadgeClass = hdk::utils::get_as_type(badge_class.clone())?;
+    let initial = ClassAdmins::initial(badge_class, &class.creator_address);
+
+    let result = hdk::get_entry_result(
+        &initial.address()?,
+        GetEntryOptions {
+            status_request: StatusRequestKind::All,
+            headers: true,
+            ..Default::default()
+        },
+    )?;
+
+    let history = match result.result {
+        GetEntryResultType::All(history) => history,
+        _ => return Ok(initial),
+    };
+
+    let at_seconds = timestamp::seconds(at)?;
+    let mut class_admins = initial;
+    let mut changed_at: Option<i64> = None;
+
+    for item in history.items {
+        let committed_at = match item.headers.first() {
+            Some(header) => timestamp::seconds(header.timestamp())?,
+            None => continue,
+        };
+
+        if committed_at > at_seconds || changed_at.map_or(false, |changed| committed_at < changed) {
+            continue;
+        }
+
+        if let Some(Entry::App(_, entry_value)) = item.entry {
+            class_admins = ClassAdmins::try_from(entry_value).map_err(|_| {
+                ZomeApiError::from(String::from(
+                    "Could not parse the administrators of the badge class",
+                ))
+            })?;
+            changed_at = Some(committed_at);
+        }
+    }
+
+    Ok(class_admins)
+}
+
+/**
+ * Gives the new version of a badge class the same administrators as the version it supersedes
+ */
+pub fn carry_over_class_admins(
+    previous_version: &Address,
+    new_version: &Address,
+) -> ZomeApiResult<()> {
+    let previous_admins = get_class_admins(previous_version)?;
+
+    if previous_admins.admins.is_empty() {
+        return Ok(());
+    }
+
+    update_class_admins(new_version, |class_admins| {
+        class_admins.admins = previous_admins.admins;
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+/**
+ * Returns whether the given agent is the current owner or an administrator of the badge class,
+ * or an admin of an organization that is
+ */
+pub fn is_class_admin(badge_class: &Address, agent_address: &Address) -> ZomeApiResult<bool> {
+    get_class_admins(badge_class)?.acts_for(agent_address, OrganizationRole::Admin)
+}
+
+/**
+ * Returns whether the given agent can issue the badge class as its owner or an administrator,
+ * including the members with the issuer role of an organization that is
+ */
+pub fn is_class_issuer(badge_class: &Address, agent_address: &Address) -> ZomeApiResult<bool> {
+    get_class_admins(badge_class)?.acts_for(agent_address, OrganizationRole::Issuer)
+}
+
+/**
+ * Returns the current owner of the family of the given badge class, which is the owner of its latest version
+ */
+pub fn get_class_owner(badge_class: &Address) -> ZomeApiResult<Address> {
+    Ok(get_class_admins(&badge_class::get_latest_version(badge_class)?)?.owner)
+}
+
+/**
+ * Returns the type of the link from the owner to the classes it owns, depending on whether it is an organization
+ */
+pub fn owner_link_type(owner: &Address) -> ZomeApiResult<&'static str> {
+    match organization::get_organization(owner)? {
+        Some(_) => Ok("organization->badge_class"),
+        None => Ok("creator->badge_class"),
+    }
+}
+
+/**
+ * Commits the initial administrators entry if needed, and updates it with the given modification
+ */
+fn update_class_admins<F>(badge_class: &Address, modify: F) -> ZomeApiResult<Address>
+where
+    F: FnOnce(&mut ClassAdmins) -> ZomeApiResult<()>,
+{
+    let class: BadgeClass = hdk::utils::get_as_type(badge_class.clone())?;
+
+    let initial = ClassAdmins::initial(badge_class, &class.creator_address);
+    let initial_entry = Entry::App("class_admins".into(), initial.clone().into());
+    let initial_address = hdk::entry_address(&initial_entry)?;
+
+    let mut class_admins = match hdk::get_entry(&initial_address)? {
+        Some(_) => hdk::utils::get_as_type(initial_address.clone())?,
+        None => {
+            hdk::commit_entry(&initial_entry)?;
+            initial
+        }
+    };
+
+    if !class_admins.acts_for(&AGENT_ADDRESS, OrganizationRole::Admin)? {
+        return Err(ZomeApiError::from(String::from(
+            "Only the owner or an administrator of a badge class can change its administrators",
+        )));
+    }
+
+    modify(&mut class_admins)?;
+
+    let new_entry = Entry::App("class_admins".into(), class_admins.into());
+    hdk::update_entry(new_entry, &initial_address)
+}
+
+/** Validation helpers */
+
+/**
+ * Only the previous owners of a badge class can remove the link that listed it among their classes
+ */
+pub fn assert_owner_link_removable(
+    owner: &Address,
+    badge_class: &Address,
+    sources: &Vec<Address>,
+) -> ZomeApiResult<()> {
+    if get_class_owner(badge_class)? == owner.clone() {
+        return Err(ZomeApiError::from(String::from(
+            "Cannot remove the link from the current owner of a badge class",
+        )));
+    }
+
+    match organization::signed_by(owner, sources, OrganizationRole::Admin)? {
+        true => Ok(()),
+        false => Err(ZomeApiError::from(String::from(
+            "Only the previous owner of a badge class can remove its link",
+        ))),
+    }
+}
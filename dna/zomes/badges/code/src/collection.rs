@@ -0,0 +1,238 @@
+use crate::badge::{self, Badge};
+use crate::visibility::{Visibility, VisibilitySetting};
+use hdk::prelude::*;
+use hdk::AGENT_ADDRESS;
+
+/**
+ * Named and ordered selection of the completed badges of an agent, to be shown at a given venue
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct Collection {
+    pub owner: Address,
+    pub name: String,
+    pub badges: Vec<Address>,
+    pub visibility: Visibility,
+}
+
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "collection",
+        description: "A collection of completed badges curated by their recipient",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: |validation_data: hdk::EntryValidationData<Collection>| {
+            match validation_data {
+                EntryValidationData::Create { entry, validation_data } => {
+                    if !validation_data.sources().contains(&entry.owner) {
+                        return Err(String::from("Only the owner of a collection can create it"));
+                    }
+
+                    assert_collection_valid(&entry)?;
+
+                    Ok(())
+                },
+                EntryValidationData::Modify {
+                    new_entry,
+                    old_entry,
+                    validation_data,
+                    ..
+                } => {
+                    if new_entry.owner != old_entry.owner {
+                        return Err(String::from("Cannot change the owner of a collection"));
+                    }
+
+                    if !validation_data.sources().contains(&old_entry.owner) {
+                        return Err(String::from("Only the owner of a collection can change it"));
+                    }
+
+                    assert_collection_valid(&new_entry)?;
+
+                    Ok(())
+                },
+                _ => Err(String::from("Cannot delete a collection")),
+            }
+        },
+        links: [
+            from!(
+                "%agent_id",
+                link_type: "owner->collection",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, .. } => {
+                            let collection: Collection = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            match collection.owner == link.link.base().clone() {
+                                true => Ok(()),
+                                false => Err(String::from("Cannot link \"owner->collection\" to the collection of another agent"))
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            )
+        ]
+    )
+}
+
+/** Handlers */
+
+pub fn create_collection(name: String, badges: Vec<Address>) -> ZomeApiResult<Address> {
+    let collection = Collection {
+        owner: AGENT_ADDRESS.clone(),
+        name,
+        badges,
+        visibility: Visibility::Public,
+    };
+    assert_collection_valid(&collection)?;
+
+    let collection_entry = Entry::App("collection".into(), collection.into());
+    let collection_address = hdk::commit_entry(&collection_entry)?;
+
+    hdk::link_entries(&AGENT_ADDRESS, &collection_address, "owner->collection", "")?;
+
+    Ok(collection_address)
+}
+
+/**
+ * Replaces the badges of the collection with the given ones, in the given order
+ */
+pub fn reorder_collection(
+    collection_address: Address,
+    badges: Vec<Address>,
+) -> ZomeApiResult<Address> {
+    update_collection(&collection_address, |collection| {
+        collection.badges = badges;
+        Ok(())
+    })
+}
+
+pub fn share_collection(
+    collection_address: Address,
+    setting: VisibilitySetting,
+) -> ZomeApiResult<Address> {
+    let initial_address = get_initial_address(&collection_address)?;
+    let visibility = Visibility::from_setting(&initial_address, setting)?;
+
+    update_collection(&collection_address, |collection| {
+        collection.visibility = visibility;
+        Ok(())
+    })
+}
+
+/**
+ * Returns the collections of the given agent that the calling agent is allowed to see
+ */
+pub fn get_collections(owner: Address) -> ZomeApiResult<Vec<Address>> {
+    let links = hdk::get_links(
+        &owner,
+        LinkMatch::Exactly("owner->collection"),
+        LinkMatch::Any,
+    )?;
+
+    let mut collections: Vec<Address> = vec![];
+    for collection_address in links.addresses() {
+        let collection: Collection = hdk::utils::get_as_type(collection_address.clone())?;
+
+        if collection
+            .visibility
+            .allows(&collection_address, &owner, &AGENT_ADDRESS)?
+        {
+            collections.push(collection_address);
+        }
+    }
+
+    Ok(collections)
+}
+
+/** Helpers */
+
+/**
+ * Returns the address the collection was created with, which is the one linked from its owner
+ * and the one its visibility grants refer to, given the address of any of its versions
+ */
+pub fn get_initial_address(collection_address: &Address) -> ZomeApiResult<Address> {
+    let collection: Collection = hdk::utils::get_as_type(collection_address.clone())?;
+    let latest_address = version_address(&collection)?;
+
+    let links = hdk::get_links(
+        &collection.owner,
+        LinkMatch::Exactly("owner->collection"),
+        LinkMatch::Any,
+    )?;
+
+    for address in links.addresses() {
+        let candidate: Collection = hdk::utils::get_as_type(address.clone())?;
+
+        if version_address(&candidate)? == latest_address {
+            return Ok(address);
+        }
+    }
+
+    Err(ZomeApiError::from(format!(
+        "Could not find the initial version of collection {}",
+        collection_address
+    )))
+}
+
+fn version_address(collection: &Collection) -> ZomeApiResult<Address> {
+    hdk::entry_address(&Entry::App("collection".into(), collection.clone().into()))
+}
+
+fn update_collection<F>(collection_address: &Address, modify: F) -> ZomeApiResult<Address>
+where
+    F: FnOnce(&mut Collection) -> ZomeApiResult<()>,
+{
+    let mut collection: Collection = hdk::utils::get_as_type(collection_address.clone())?;
+
+    if collection.owner != AGENT_ADDRESS.clone() {
+        return Err(ZomeApiError::from(String::from(
+            "Only the owner of a collection can change it",
+        )));
+    }
+
+    modify(&mut collection)?;
+    assert_collection_valid(&collection)?;
+
+    let new_entry = Entry::App("collection".into(), collection.into());
+    hdk::update_entry(new_entry, collection_address)
+}
+
+/** Validation helpers */
+
+/**
+ * Asserts that the collection has a name and only contains distinct badges that its owner has completed
+ */
+pub fn assert_collection_valid(collection: &Collection) -> ZomeApiResult<()> {
+    if collection.name.trim().is_empty() {
+        return Err(ZomeApiError::from(String::from(
+            "Collection name cannot be empty",
+        )));
+    }
+
+    for (index, badge_address) in collection.badges.iter().enumerate() {
+        if collection.badges[..index].contains(badge_address) {
+            return Err(ZomeApiError::from(format!(
+                "Badge {} is included more than once in the collection",
+                badge_address
+            )));
+        }
+
+        let badge: Badge = hdk::utils::get_as_type(badge_address.clone())?;
+
+        if badge.recipient != collection.owner {
+            return Err(ZomeApiError::from(format!(
+                "Badge {} is not held by the owner of the collection",
+                badge_address
+            )));
+        }
+
+        badge::assert_badge_completed(&badge.badge_class, &collection.owner)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,138 @@
+use crate::attestation;
+use crate::badge::Badge;
+use crate::badge_class::BadgeClass;
+use hdk::prelude::*;
+
+/**
+ * Rubric criterion of a badge class that issuers attest to when claiming
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Criterion {
+    pub id: String,
+    pub text: String,
+    pub required: bool,
+}
+
+/**
+ * Issuers of a badge that attested to a criterion, and whether they reach the validators of the class
+ */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CriterionCoverage {
+    pub criterion: Criterion,
+    pub attested_by: Vec<Address>,
+    pub satisfied: bool,
+}
+
+/** Handlers */
+
+pub fn get_criteria_coverage(
+    recipient: Address,
+    badge_class: Address,
+) -> ZomeApiResult<Vec<CriterionCoverage>> {
+    let class: BadgeClass = hdk::utils::get_as_type(badge_class.clone())?;
+    let badge_address = Badge::initial(&recipient, &badge_class).address()?;
+
+    let badge = match hdk::get_entry(&badge_address)? {
+        Some(_) => hdk::utils::get_as_type(badge_address)?,
+        None => Badge::initial(&recipient, &badge_class),
+    };
+
+    get_coverage(&class, &badge)
+}
+
+/** Helpers */
+
+/**
+ * Returns the coverage of every criterion of the class by the attestations of the issuers of the badge
+ */
+pub fn get_coverage(class: &BadgeClass, badge: &Badge) -> ZomeApiResult<Vec<CriterionCoverage>> {
+    let badge_address = Badge::initial(&badge.recipient, &badge.badge_class).address()?;
+    let attestations = attestation::get_badge_attestations(&badge_address)?;
+
+    Ok(class
+        .criteria
+        .iter()
+        .map(|criterion| {
+            let mut attested_by: Vec<Address> = vec![];
+
+            for attestation in attestations.iter() {
+                let issuer = &attestation.attestation.issuer;
+
+                if badge.issuers.contains(issuer)
+                    && !attested_by.contains(issuer)
+                    && attestation.attestation.criteria.contains(&criterion.id)
+                {
+                    attested_by.push(issuer.clone());
+                }
+            }
+
+            CriterionCoverage {
+                criterion: criterion.clone(),
+                satisfied: attested_by.len() >= class.validators,
+                attested_by,
+            }
+        })
+        .collect())
+}
+
+/** Validation helpers */
+
+/**
+ * Asserts that the criteria of a class are well formed
+ */
+pub fn assert_criteria_valid(criteria: &Vec<Criterion>) -> ZomeApiResult<()> {
+    for (index, criterion) in criteria.iter().enumerate() {
+        if criterion.id.is_empty() {
+            return Err(ZomeApiError::from(String::from(
+                "The id of a criterion cannot be empty",
+            )));
+        }
+
+        if criteria[..index].iter().any(|c| c.id == criterion.id) {
+            return Err(ZomeApiError::from(format!(
+                "Criterion id {} is repeated",
+                criterion.id
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/**
+ * Asserts that the attested criteria are all defined in the class
+ */
+pub fn assert_criteria_exist(class: &BadgeClass, criteria: &Vec<String>) -> ZomeApiResult<()> {
+    for criterion_id in criteria {
+        if !class.criteria.iter().any(|criterion| criterion.id == criterion_id.clone()) {
+            return Err(ZomeApiError::from(format!(
+                "Criterion {} is not defined in badge class {}",
+                criterion_id, class.name
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/**
+ * Asserts that every required criterion of the class has been attested by as many issuers as validators
+ */
+pub fn assert_required_criteria_covered(class: &BadgeClass, badge: &Badge) -> ZomeApiResult<()> {
+    if !class.criteria.iter().any(|criterion| criterion.required) {
+        return Ok(());
+    }
+
+    for coverage in get_coverage(class, badge)? {
+        if coverage.criterion.required && !coverage.satisfied {
+            return Err(ZomeApiError::from(format!(
+                "Required criterion {} of badge {} has only been attested by {} issuers",
+                coverage.criterion.id,
+                class.name,
+                coverage.attested_by.len()
+            )));
+        }
+    }
+
+    Ok(())
+}
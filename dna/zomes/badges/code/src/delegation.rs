@@ -0,0 +1,266 @@
+use crate::badge_class::{self, BadgeClass};
+use crate::timestamp;
+use hdk::holochain_core_types::time::Iso8601;
+use hdk::prelude::*;
+use hdk::AGENT_ADDRESS;
+
+/**
+ * Right granted by an issuer to another agent, typically an automated one,
+ * to claim badges of the given classes on their behalf until it expires
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct Delegation {
+    pub delegator: Address,
+    pub delegate: Address,
+    pub badge_classes: Vec<Address>,
+    pub expires_at: Iso8601,
+}
+
+impl Delegation {
+    /**
+     * Returns whether the delegation includes the given badge class or any other version of it
+     */
+    pub fn covers(&self, badge_class: &Address) -> ZomeApiResult<bool> {
+        Ok(badge_class::get_class_versions(badge_class)?
+            .iter()
+            .any(|version| self.badge_classes.contains(version)))
+    }
+
+    pub fn allows(&self, badge_class: &Address, at: &Iso8601) -> ZomeApiResult<bool> {
+        Ok(self.covers(badge_class)?
+            && timestamp::seconds(at)? < timestamp::seconds(&self.expires_at)?)
+    }
+}
+
+/**
+ * Revocation of a delegation by its delegator: claims made by the delegate after it was committed are no longer valid
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct DelegationRevocation {
+    pub delegation: Address,
+}
+
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "delegation",
+        description: "The right of an agent to issue badges on behalf of another issuer",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: |validation_data: hdk::EntryValidationData<Delegation>| {
+            match validation_data {
+                EntryValidationData::Create { entry, validation_data } => {
+                    if !validation_data.sources().contains(&entry.delegator) {
+                        return Err(String::from("Only the delegator can grant a delegation"));
+                    }
+
+                    if entry.delegate == entry.delegator {
+                        return Err(String::from("Agents cannot delegate to themselves"));
+                    }
+
+                    if entry.badge_classes.is_empty() {
+                        return Err(String::from("A delegation must be scoped to at least one badge class"));
+                    }
+
+                    for badge_class in entry.badge_classes.iter() {
+                        hdk::utils::get_as_type::<BadgeClass>(badge_class.clone())?;
+                    }
+
+                    let granted_at = validation_data.package.chain_header.timestamp();
+                    if timestamp::seconds(&entry.expires_at)? <= timestamp::seconds(granted_at)? {
+                        return Err(String::from("A delegation must expire after it is granted"));
+                    }
+
+                    Ok(())
+                },
+                _ => Err(String::from("Cannot update or delete a delegation")),
+            }
+        },
+        links: [
+            from!(
+                "%agent_id",
+                link_type: "delegate->delegation",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, .. } => {
+                            let delegation: Delegation = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            match delegation.delegate == link.link.base().clone() {
+                                true => Ok(()),
+                                false => Err(String::from("Cannot link \"delegate->delegation\" from an agent who is not the delegate"))
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            )
+        ]
+    )
+}
+
+pub fn revocation_entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "delegation_revocation",
+        description: "The revocation of a delegation by its delegator",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: |validation_data: hdk::EntryValidationData<DelegationRevocation>| {
+            match validation_data {
+                EntryValidationData::Create { entry, validation_data } => {
+                    let delegation: Delegation = hdk::utils::get_as_type(entry.delegation.clone())?;
+
+                    match validation_data.sources().contains(&delegation.delegator) {
+                        true => Ok(()),
+                        false => Err(String::from("Only the delegator can revoke a delegation")),
+                    }
+                },
+                _ => Err(String::from("Cannot update or delete the revocation of a delegation")),
+            }
+        },
+        links: [
+            from!(
+                "delegation",
+                link_type: "delegation->revocation",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, .. } => {
+                            let revocation: DelegationRevocation = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            match revocation.delegation == link.link.base().clone() {
+                                true => Ok(()),
+                                false => Err(String::from("Cannot link \"delegation->revocation\" to the revocation of another delegation"))
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            )
+        ]
+    )
+}
+
+/** Handlers */
+
+pub fn delegate_issuing(
+    delegate: Address,
+    badge_classes: Vec<Address>,
+    expires_at: Iso8601,
+) -> ZomeApiResult<Address> {
+    let delegation = Delegation {
+        delegator: AGENT_ADDRESS.clone(),
+        delegate: delegate.clone(),
+        badge_classes,
+        expires_at,
+    };
+
+    let delegation_entry = Entry::App("delegation".into(), delegation.into());
+    let delegation_address = hdk::commit_entry(&delegation_entry)?;
+
+    hdk::link_entries(&delegate, &delegation_address, "delegate->delegation", "")?;
+
+    Ok(delegation_address)
+}
+
+/**
+ * Revokes the given delegation of the calling agent, keeping valid the claims that its delegate already made
+ */
+pub fn revoke_delegation(delegation_address: Address) -> ZomeApiResult<Address> {
+    let delegation: Delegation = hdk::utils::get_as_type(delegation_address.clone())?;
+
+    if delegation.delegator != AGENT_ADDRESS.clone() {
+        return Err(ZomeApiError::from(String::from(
+            "Only the delegator can revoke a delegation",
+        )));
+    }
+
+    let revocation = DelegationRevocation {
+        delegation: delegation_address.clone(),
+    };
+    let revocation_entry = Entry::App("delegation_revocation".into(), revocation.into());
+    let revocation_address = hdk::commit_entry(&revocation_entry)?;
+
+    hdk::link_entries(
+        &delegation_address,
+        &revocation_address,
+        "delegation->revocation",
+        "",
+    )?;
+
+    Ok(revocation_address)
+}
+
+/**
+ * Returns all the delegations granted to the given agent, including the expired and revoked ones
+ */
+pub fn get_delegations_to(delegate: Address) -> ZomeApiResult<Vec<Delegation>> {
+    hdk::utils::get_links_and_load_type(
+        &delegate,
+        LinkMatch::Exactly("delegate->delegation"),
+        LinkMatch::Any,
+    )
+}
+
+/** Validation helpers */
+
+/**
+ * Asserts that the delegator had granted the delegate the right to issue the badge class at the given time,
+ * and had not revoked it yet
+ */
+pub fn assert_delegated(
+    delegator: &Address,
+    delegate: &Address,
+    badge_class: &Address,
+    at: &Iso8601,
+) -> ZomeApiResult<()> {
+    let links = hdk::get_links(
+        delegate,
+        LinkMatch::Exactly("delegate->delegation"),
+        LinkMatch::Any,
+    )?;
+
+    for delegation_address in links.addresses() {
+        let delegation: Delegation = hdk::utils::get_as_type(delegation_address.clone())?;
+
+        if delegation.delegator == delegator.clone()
+            && delegation.allows(badge_class, at)?
+            && !revoked_before(&delegation_address, at)?
+        {
+            return Ok(());
+        }
+    }
+
+    Err(ZomeApiError::from(format!(
+        "Agent {} has no valid delegation from {} to issue badge class {}",
+        delegate, delegator, badge_class
+    )))
+}
+
+/**
+ * Returns whether any revocation of the delegation was committed before the given time
+ */
+fn revoked_before(delegation_address: &Address, at: &Iso8601) -> ZomeApiResult<bool> {
+    let links = hdk::get_links(
+        delegation_address,
+        LinkMatch::Exactly("delegation->revocation"),
+        LinkMatch::Any,
+    )?;
+
+    for revocation_address in links.addresses() {
+        if let Some(revoked_at) = timestamp::latest_entry_time(&revocation_address)? {
+            if timestamp::seconds(&revoked_at)? <= timestamp::seconds(at)? {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
@@ -0,0 +1,216 @@
+use crate::badge;
+use crate::badge_class::BadgeClass;
+use crate::organization::{self, OrganizationRole};
+use hdk::prelude::*;
+use hdk::AGENT_ADDRESS;
+
+/**
+ * Third party statement vouching for the quality of a badge class or the trustworthiness of an issuer,
+ * either an agent or an organization
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct Endorsement {
+    pub target: Address,
+    pub target_type: EndorsementTargetType,
+    pub endorser: Address,
+    pub claim: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum EndorsementTargetType {
+    BadgeClass,
+    Issuer,
+}
+
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "endorsement",
+        description: "An endorsement of a badge class or an issuer by a third party",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: |validation_data: hdk::EntryValidationData<Endorsement>| {
+            match validation_data {
+                EntryValidationData::Create { entry, validation_data } => {
+                    if !validation_data.sources().contains(&entry.endorser) {
+                        return Err(String::from("Only the endorser can sign their endorsement"));
+                    }
+
+                    if entry.target == entry.endorser {
+                        return Err(String::from("Agents cannot endorse themselves"));
+                    }
+
+                    match entry.target_type {
+                        EndorsementTargetType::BadgeClass => {
+                            let badge_class: BadgeClass = hdk::utils::get_as_type(entry.target.clone())?;
+
+                            if organization::acts_for(&badge_class.creator_address, &entry.endorser, OrganizationRole::Member)? {
+                                return Err(String::from("The creator of a badge class, or a member of the creating organization, cannot endorse it"));
+                            }
+                        },
+                        EndorsementTargetType::Issuer => match hdk::get_entry(&entry.target)? {
+                            Some(Entry::AgentId(_)) => (),
+                            _ => match organization::get_organization(&entry.target)? {
+                                Some(target_organization) => {
+                                    if target_organization.has_role(&entry.endorser, &OrganizationRole::Member) {
+                                        return Err(String::from("Members of an organization cannot endorse it"));
+                                    }
+                                },
+                                None => return Err(String::from("The target of an issuer endorsement must be an agent or an organization")),
+                            },
+                        },
+                    }
+
+                    if let Some(endorser_badge_class) = get_endorser_badge_class()? {
+                        badge::assert_holder_valid(&endorser_badge_class, &entry.endorser)?;
+                    }
+
+                    Ok(())
+                },
+                _ => Err(String::from("Cannot update or delete an endorsement")),
+            }
+        },
+        links: [
+            from!(
+                "badge_class",
+                link_type: "badge_class->endorsement",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, .. } => {
+                            let endorsement: Endorsement = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            match endorsement.target == link.link.base().clone() {
+                                true => Ok(()),
+                                false => Err(String::from("Cannot link \"badge_class->endorsement\" to an endorsement of another target"))
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            ),
+            from!(
+                "%agent_id",
+                link_type: "issuer->endorsement",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, .. } => {
+                            let endorsement: Endorsement = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            match endorsement.target == link.link.base().clone() {
+                                true => Ok(()),
+                                false => Err(String::from("Cannot link \"issuer->endorsement\" to an endorsement of another target"))
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            ),
+            from!(
+                "organization",
+                link_type: "organization->endorsement",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, .. } => {
+                            let endorsement: Endorsement = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            match endorsement.target == link.link.base().clone() {
+                                true => Ok(()),
+                                false => Err(String::from("Cannot link \"organization->endorsement\" to an endorsement of another target"))
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            )
+        ]
+    )
+}
+
+/** Handlers */
+
+pub fn endorse(
+    target: Address,
+    target_type: EndorsementTargetType,
+    claim: String,
+) -> ZomeApiResult<Address> {
+    let link_type = match target_type {
+        EndorsementTargetType::BadgeClass => "badge_class->endorsement",
+        EndorsementTargetType::Issuer => match organization::get_organization(&target)? {
+            Some(_) => "organization->endorsement",
+            None => "issuer->endorsement",
+        },
+    };
+
+    let endorsement = Endorsement {
+        target: target.clone(),
+        target_type,
+        endorser: AGENT_ADDRESS.clone(),
+        claim,
+    };
+
+    let endorsement_entry = Entry::App("endorsement".into(), endorsement.into());
+    let endorsement_address = hdk::commit_entry(&endorsement_entry)?;
+
+    hdk::link_entries(&target, &endorsement_address, link_type, "")?;
+
+    Ok(endorsement_address)
+}
+
+/**
+ * Returns the endorsements of the given badge class or issuer, which may be an agent or an organization
+ */
+pub fn get_endorsements(target: Address) -> ZomeApiResult<Vec<Endorsement>> {
+    let mut endorsements: Vec<Endorsement> = vec![];
+
+    for link_type in [
+        "badge_class->endorsement",
+        "issuer->endorsement",
+        "organization->endorsement",
+    ]
+    .iter()
+    {
+        let mut target_endorsements: Vec<Endorsement> = hdk::utils::get_links_and_load_type(
+            &target,
+            LinkMatch::Exactly(link_type),
+            LinkMatch::Any,
+        )?;
+
+        endorsements.append(&mut target_endorsements);
+    }
+
+    Ok(endorsements)
+}
+
+/** Helpers */
+
+/**
+ * Returns the badge class that endorsers must hold, if the "endorser_badge_class" property of the DNA
+ * is set to its address instead of null
+ */
+pub fn get_endorser_badge_class() -> ZomeApiResult<Option<Address>> {
+    let endorser_badge_class_json = match hdk::property("endorser_badge_class") {
+        Ok(json) => json,
+        Err(_) => return Ok(None),
+    };
+
+    let endorser_badge_class: Result<Option<Address>, _> =
+        serde_json::from_str(&endorser_badge_class_json.to_string());
+
+    match endorser_badge_class {
+        Ok(badge_class) => Ok(badge_class),
+        Err(_) => Err(ZomeApiError::from(String::from(
+            "Could not get the endorser badge class",
+        ))),
+    }
+}
@@ -0,0 +1,167 @@
+use crate::class_admins;
+use hdk::prelude::*;
+use hdk::AGENT_ADDRESS;
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct IssuerAllowlist {
+    pub badge_class: Address,
+    pub issuers: Vec<Address>,
+}
+
+impl IssuerAllowlist {
+    /**
+     * Returns the initial empty allowlist for the given class
+     */
+    pub fn initial(badge_class: &Address) -> IssuerAllowlist {
+        IssuerAllowlist {
+            badge_class: badge_class.clone(),
+            issuers: vec![],
+        }
+    }
+
+    pub fn address(&self) -> ZomeApiResult<Address> {
+        let entry = Entry::App("issuer_allowlist".into(), self.clone().into());
+
+        hdk::entry_address(&entry)
+    }
+}
+
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "issuer_allowlist",
+        description: "The agents allowed to issue a badge class with the allowlist issuance policy",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: |validation_data: hdk::EntryValidationData<IssuerAllowlist>| {
+            match validation_data {
+                EntryValidationData::Create { entry, .. } => {
+                    if entry.issuers.len() > 0 {
+                        return Err(String::from("No issuers can be present when creating an issuer allowlist"));
+                    }
+
+                    Ok(())
+                },
+                EntryValidationData::Modify {
+                    new_entry,
+                    old_entry,
+                    validation_data,
+                    ..
+                } => {
+                    if new_entry.badge_class != old_entry.badge_class {
+                        return Err(String::from("Cannot change the class of an issuer allowlist"));
+                    }
+
+                    let author = &validation_data.sources()[0];
+
+                    match class_admins::is_class_admin(&new_entry.badge_class, author)? {
+                        true => Ok(()),
+                        false => Err(String::from("Only the owner or an administrator of a badge class can change its issuer allowlist"))
+                    }
+                },
+                _ => Err(String::from("Cannot delete an issuer allowlist")),
+            }
+        },
+        links: []
+    )
+}
+
+/** Handlers */
+
+pub fn add_allowed_issuer(badge_class: Address, issuer: Address) -> ZomeApiResult<Address> {
+    update_allowlist(&badge_class, |allowlist| {
+        if allowlist.issuers.contains(&issuer) {
+            return Err(ZomeApiError::from(format!(
+                "Agent {} is already allowed to issue the badge class",
+                issuer
+            )));
+        }
+
+        allowlist.issuers.push(issuer.clone());
+        Ok(())
+    })
+}
+
+pub fn remove_allowed_issuer(badge_class: Address, issuer: Address) -> ZomeApiResult<Address> {
+    update_allowlist(&badge_class, |allowlist| {
+        if !allowlist.issuers.contains(&issuer) {
+            return Err(ZomeApiError::from(format!(
+                "Agent {} is not allowed to issue the badge class",
+                issuer
+            )));
+        }
+
+        allowlist.issuers.retain(|i| i.clone() != issuer);
+        Ok(())
+    })
+}
+
+/** Helpers */
+
+/**
+ * Returns the agents currently allowed to issue the given badge class
+ */
+pub fn get_allowed_issuers(badge_class: &Address) -> ZomeApiResult<Vec<Address>> {
+    let initial_address = IssuerAllowlist::initial(badge_class).address()?;
+
+    match hdk::get_entry(&initial_address)? {
+        Some(_) => {
+            let allowlist: IssuerAllowlist = hdk::utils::get_as_type(initial_address)?;
+            Ok(allowlist.issuers)
+        }
+        None => Ok(vec![]),
+    }
+}
+
+/**
+ * Gives the new version of a badge class the same allowed issuers as the version it supersedes
+ */
+pub fn carry_over_allowed_issuers(
+    previous_version: &Address,
+    new_version: &Address,
+) -> ZomeApiResult<()> {
+    let previous_issuers = get_allowed_issuers(previous_version)?;
+
+    if previous_issuers.is_empty() {
+        return Ok(());
+    }
+
+    update_allowlist(new_version, |allowlist| {
+        allowlist.issuers = previous_issuers;
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+/**
+ * Commits the initial allowlist entry if needed, and updates it with the given modification
+ */
+fn update_allowlist<F>(badge_class: &Address, modify: F) -> ZomeApiResult<Address>
+where
+    F: FnOnce(&mut IssuerAllowlist) -> ZomeApiResult<()>,
+{
+    if !class_admins::is_class_admin(badge_class, &AGENT_ADDRESS)? {
+        return Err(ZomeApiError::from(String::from(
+            "Only the owner or an administrator of a badge class can change its issuer allowlist",
+        )));
+    }
+
+    let initial = IssuerAllowlist::initial(badge_class);
+    let initial_entry = Entry::App("issuer_allowlist".into(), initial.clone().into());
+    let initial_address = hdk::entry_address(&initial_entry)?;
+
+    let mut allowlist = match hdk::get_entry(&initial_address)? {
+        Some(_) => hdk::utils::get_as_type(initial_address.clone())?,
+        None => {
+            hdk::commit_entry(&initial_entry)?;
+            initial
+        }
+    };
+
+    modify(&mut allowlist)?;
+
+    let new_entry = Entry::App("issuer_allowlist".into(), allowlist.into());
+    hdk::update_entry(new_entry, &initial_address)
+}
@@ -1,4 +1,6 @@
 #![feature(proc_macro_hygiene)]
+extern crate anchors;
+extern crate chrono;
 extern crate hdk;
 extern crate hdk_proc_macros;
 extern crate serde;
@@ -6,6 +8,7 @@ extern crate serde_derive;
 extern crate serde_json;
 
 use hdk::holochain_core_types::entry::Entry;
+use hdk::holochain_core_types::time::Iso8601;
 use hdk::holochain_persistence_api::cas::content::Address;
 use hdk::prelude::*;
 use hdk::{entry_definition::ValidatingEntryType, error::ZomeApiResult, AGENT_ADDRESS};
@@ -13,12 +16,51 @@ use hdk_proc_macros::zome;
 
 // see https://developer.holochain.org/api/0.0.40-alpha1/hdk/ for info on using the hdk library
 
+pub mod alignment;
+pub mod analysis;
+pub mod anchor;
+pub mod application;
+pub mod attestation;
 pub mod badge;
 pub mod badge_class;
-pub mod anchor;
-
+pub mod class_admins;
+pub mod collection;
+pub mod criteria;
+pub mod delegation;
+pub mod endorsement;
+pub mod issuer_allowlist;
+pub mod lineage;
+pub mod notification;
+pub mod open_badges;
+pub mod organization;
+pub mod pathway;
+pub mod private_evidence;
+pub mod search;
+pub mod tag;
+pub mod tier_family;
+pub mod timestamp;
+pub mod visibility;
+
+use alignment::Alignment;
+use analysis::IssuanceReport;
+use application::ApplicationWithStatus;
+use attestation::{AttestationWithTimestamp, AttestedBadge, Confidence};
 use badge::Badge;
-use badge_class::BadgeClass;
+use badge_class::{BadgeClass, BadgeClassKind, ClassBadges, IssuancePolicy};
+use class_admins::ClassAdmins;
+use criteria::{Criterion, CriterionCoverage};
+use delegation::Delegation;
+use endorsement::{Endorsement, EndorsementTargetType};
+use lineage::{BadgeDescendant, LineageNode};
+use notification::NotificationWithAddress;
+use open_badges::{OpenBadgeClass, OpenBadgeCollection};
+use organization::{Organization, OrganizationRole};
+use pathway::PathwayEligibility;
+use private_evidence::EvidenceOpening;
+use search::SearchResult;
+use tag::TagCount;
+use tier_family::AgentTier;
+use visibility::{Visibility, VisibilitySetting};
 
 #[zome]
 mod my_zome {
@@ -33,8 +75,18 @@ mod my_zome {
         Ok(())
     }
 
+    #[receive]
+    pub fn receive(from: Address, msg_json: JsonString) -> String {
+        notification::receive_notification(from, msg_json)
+    }
+
     #[entry_def]
     fn anchor() -> ValidatingEntryType {
+        anchor::legacy_entry_def()
+    }
+
+    #[entry_def]
+    fn badge_anchor() -> ValidatingEntryType {
         anchor::entry_def()
     }
 
@@ -48,6 +100,91 @@ mod my_zome {
         badge::entry_def()
     }
 
+    #[entry_def]
+    fn attestation() -> ValidatingEntryType {
+        attestation::entry_def()
+    }
+
+    #[entry_def]
+    fn class_admins() -> ValidatingEntryType {
+        class_admins::entry_def()
+    }
+
+    #[entry_def]
+    fn issuer_allowlist() -> ValidatingEntryType {
+        issuer_allowlist::entry_def()
+    }
+
+    #[entry_def]
+    fn tier_family() -> ValidatingEntryType {
+        tier_family::entry_def()
+    }
+
+    #[entry_def]
+    fn badge_application() -> ValidatingEntryType {
+        application::application_entry_def()
+    }
+
+    #[entry_def]
+    fn application_review() -> ValidatingEntryType {
+        application::review_entry_def()
+    }
+
+    #[entry_def]
+    fn endorsement() -> ValidatingEntryType {
+        endorsement::entry_def()
+    }
+
+    #[entry_def]
+    fn delegation() -> ValidatingEntryType {
+        delegation::entry_def()
+    }
+
+    #[entry_def]
+    fn delegation_revocation() -> ValidatingEntryType {
+        delegation::revocation_entry_def()
+    }
+
+    #[entry_def]
+    fn organization() -> ValidatingEntryType {
+        organization::entry_def()
+    }
+
+    #[entry_def]
+    fn private_evidence() -> ValidatingEntryType {
+        private_evidence::private_entry_def()
+    }
+
+    #[entry_def]
+    fn evidence_opening() -> ValidatingEntryType {
+        private_evidence::opening_entry_def()
+    }
+
+    #[entry_def]
+    fn evidence_commitment() -> ValidatingEntryType {
+        private_evidence::commitment_entry_def()
+    }
+
+    #[entry_def]
+    fn badge_visibility() -> ValidatingEntryType {
+        visibility::entry_def()
+    }
+
+    #[entry_def]
+    fn visibility_grant() -> ValidatingEntryType {
+        visibility::grant_entry_def()
+    }
+
+    #[entry_def]
+    fn collection() -> ValidatingEntryType {
+        collection::entry_def()
+    }
+
+    #[entry_def]
+    fn notification() -> ValidatingEntryType {
+        notification::entry_def()
+    }
+
     #[zome_fn("hc_public")]
     fn get_entry(address: Address) -> ZomeApiResult<Option<Entry>> {
         hdk::get_entry(&address)
@@ -59,9 +196,16 @@ mod my_zome {
     }
 
     #[zome_fn("hc_public")]
-    fn get_badge(recipient: Address, badge_class: Address) -> ZomeApiResult<Option<Entry>> {
-        let badge = Badge::initial(&recipient, &badge_class);
-        hdk::get_entry(&badge.address()?)
+    fn get_badge(recipient: Address, badge_class: Address) -> ZomeApiResult<Option<AttestedBadge>> {
+        for version in badge_class::get_class_versions(&badge_class)?.iter().rev() {
+            let badge_address = Badge::initial(&recipient, version).address()?;
+
+            if let Some(_) = hdk::get_entry(&badge_address)? {
+                return attestation::get_attested_badge(&badge_address).map(Some);
+            }
+        }
+
+        Ok(None)
     }
 
     #[zome_fn("hc_public")]
@@ -71,68 +215,62 @@ mod my_zome {
 
     #[zome_fn("hc_public")]
     fn get_all_badge_classes() -> ZomeApiResult<Vec<Address>> {
-        let links = hdk::get_links(
-            &anchor::address()?,
-            LinkMatch::Exactly("anchor->badge_class"),
-            LinkMatch::Any,
-        )?;
-
-        Ok(links.addresses())
+        anchor::badge_classes()?
+            .map(|class_address| {
+                class_address.and_then(|address| badge_class::get_latest_version(&address))
+            })
+            .collect()
     }
 
     #[zome_fn("hc_public")]
-    fn get_badges_for_class(badge_class: Address) -> ZomeApiResult<Vec<Address>> {
-        let links = hdk::get_links(
-            &badge_class,
-            LinkMatch::Exactly("badge_class->badge"),
-            LinkMatch::Any,
-        )?;
-
-        Ok(links.addresses())
+    fn get_badges_for_class(badge_class: Address) -> ZomeApiResult<ClassBadges> {
+        badge_class::get_badges_for_class(badge_class)
     }
 
     #[zome_fn("hc_public")]
     fn get_badges_to_recipient(agent_address: Address) -> ZomeApiResult<Vec<Address>> {
-        let links = hdk::get_links(
-            &agent_address,
-            LinkMatch::Exactly("recipient->badge"),
-            LinkMatch::Exactly("completed"),
-        )?;
-
-        Ok(links.addresses())
+        visibility::get_visible_badges_to_recipient(&agent_address, "completed")
     }
 
     #[zome_fn("hc_public")]
     fn get_temptative_badges_to_recipient(agent_address: Address) -> ZomeApiResult<Vec<Address>> {
-        let links = hdk::get_links(
-            &agent_address,
-            LinkMatch::Exactly("recipient->badge"),
-            LinkMatch::Exactly("temptative"),
-        )?;
-
-        Ok(links.addresses())
+        visibility::get_visible_badges_to_recipient(&agent_address, "temptative")
     }
 
     #[zome_fn("hc_public")]
-    fn get_badges_from_issuer(agent_address: Address) -> ZomeApiResult<Vec<Address>> {
+    fn get_badges_from_issuer(agent_address: Address) -> ZomeApiResult<Vec<AttestedBadge>> {
         let links = hdk::get_links(
             &agent_address,
             LinkMatch::Exactly("issuer->badge"),
             LinkMatch::Any,
         )?;
 
-        Ok(links.addresses())
+        visibility::filter_visible_badges(links.addresses())?
+            .iter()
+            .map(attestation::get_attested_badge)
+            .collect()
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_attestations_from_issuer(
+        agent_address: Address,
+    ) -> ZomeApiResult<Vec<AttestationWithTimestamp>> {
+        attestation::get_attestations_from_issuer(agent_address)
     }
 
     #[zome_fn("hc_public")]
     fn get_created_badges(agent_address: Address) -> ZomeApiResult<Vec<Address>> {
-        let links = hdk::get_links(
-            &agent_address,
-            LinkMatch::Exactly("creator->badge_class"),
-            LinkMatch::Any,
-        )?;
+        let mut created: Vec<Address> = vec![];
+
+        for link_type in ["creator->badge_class", "organization->badge_class"].iter() {
+            let links = hdk::get_links(&agent_address, LinkMatch::Exactly(link_type), LinkMatch::Any)?;
+            created.extend(links.addresses());
+        }
 
-        Ok(links.addresses())
+        created
+            .iter()
+            .map(badge_class::get_latest_version)
+            .collect()
     }
 
     #[zome_fn("hc_public")]
@@ -141,6 +279,97 @@ mod my_zome {
         description: String,
         image: String,
         validators: usize,
+        min_lineages: Option<usize>,
+        issuance_policy: Option<IssuancePolicy>,
+        max_holders: Option<usize>,
+        issuance_opens_at: Option<Iso8601>,
+        issuance_closes_at: Option<Iso8601>,
+        kind: Option<BadgeClassKind>,
+        criteria: Option<Vec<Criterion>>,
+        alignments: Option<Vec<Alignment>>,
+        tags: Option<Vec<String>>,
+        organization: Option<Address>,
+    ) -> ZomeApiResult<Address> {
+        let class = BadgeClass {
+            name,
+            description,
+            image,
+            creator_address: organization.unwrap_or(AGENT_ADDRESS.clone()),
+            validators,
+            min_lineages,
+            supersedes: None,
+            issuance_policy: issuance_policy.unwrap_or_default(),
+            max_holders,
+            issuance_opens_at,
+            issuance_closes_at,
+            kind: kind.unwrap_or_default(),
+            criteria: criteria.unwrap_or_default(),
+            alignments: alignments.unwrap_or_default(),
+            tags: tags
+                .unwrap_or_default()
+                .iter()
+                .map(tag::normalize_tag)
+                .collect(),
+        };
+
+        badge_class::create_badge_class(class)
+    }
+
+    #[zome_fn("hc_public")]
+    fn claim_agent_deserves_badge(
+        recipient: Address,
+        badge_class: Address,
+        evidences: Vec<Address>,
+        statement: String,
+        confidence: Confidence,
+        criteria: Option<Vec<String>>,
+        on_behalf_of: Option<Address>,
+    ) -> ZomeApiResult<Address> {
+        badge::claim_agent_deserves_badge(
+            recipient,
+            badge_class,
+            evidences,
+            statement,
+            confidence,
+            criteria.unwrap_or_default(),
+            on_behalf_of,
+        )
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_badge_lineage(recipient: Address, badge_class: Address) -> ZomeApiResult<LineageNode> {
+        lineage::get_badge_lineage(recipient, badge_class)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_badge_descendants(
+        agent_address: Address,
+        badge_class: Address,
+    ) -> ZomeApiResult<Vec<BadgeDescendant>> {
+        lineage::get_badge_descendants(agent_address, badge_class)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_issuance_report(badge_class: Address) -> ZomeApiResult<IssuanceReport> {
+        analysis::get_issuance_report(badge_class)
+    }
+
+    #[zome_fn("hc_public")]
+    fn create_badge_class_version(
+        previous_version: Address,
+        name: String,
+        description: String,
+        image: String,
+        validators: usize,
+        min_lineages: Option<usize>,
+        issuance_policy: Option<IssuancePolicy>,
+        max_holders: Option<usize>,
+        issuance_opens_at: Option<Iso8601>,
+        issuance_closes_at: Option<Iso8601>,
+        kind: Option<BadgeClassKind>,
+        criteria: Option<Vec<Criterion>>,
+        alignments: Option<Vec<Alignment>>,
+        tags: Option<Vec<String>>,
     ) -> ZomeApiResult<Address> {
         let class = BadgeClass {
             name,
@@ -148,34 +377,301 @@ mod my_zome {
             image,
             creator_address: AGENT_ADDRESS.clone(),
             validators,
+            min_lineages,
+            supersedes: None,
+            issuance_policy: issuance_policy.unwrap_or_default(),
+            max_holders,
+            issuance_opens_at,
+            issuance_closes_at,
+            kind: kind.unwrap_or_default(),
+            criteria: criteria.unwrap_or_default(),
+            alignments: alignments.unwrap_or_default(),
+            tags: tags
+                .unwrap_or_default()
+                .iter()
+                .map(tag::normalize_tag)
+                .collect(),
         };
 
-        let class_entry = Entry::App("badge_class".into(), class.into());
-        let class_address = hdk::commit_entry(&class_entry)?;
+        badge_class::create_badge_class_version(previous_version, class)
+    }
 
-        hdk::link_entries(
-            &anchor::address()?,
-            &class_address,
-            "anchor->badge_class",
-            "",
-        )?;
+    #[zome_fn("hc_public")]
+    fn get_badge_class_versions(badge_class: Address) -> ZomeApiResult<Vec<Address>> {
+        badge_class::get_class_versions(&badge_class)
+    }
 
-        hdk::link_entries(
-            &AGENT_ADDRESS.clone(),
-            &class_address,
-            "creator->badge_class",
-            "",
-        )?;
+    #[zome_fn("hc_public")]
+    fn get_class_admins(badge_class: Address) -> ZomeApiResult<ClassAdmins> {
+        class_admins::get_class_admins(&badge_class)
+    }
 
-        Ok(class_address)
+    #[zome_fn("hc_public")]
+    fn add_class_admin(badge_class: Address, admin: Address) -> ZomeApiResult<Address> {
+        class_admins::add_class_admin(badge_class, admin)
     }
 
     #[zome_fn("hc_public")]
-    fn claim_agent_deserves_badge(
-        recipient: Address,
+    fn remove_class_admin(badge_class: Address, admin: Address) -> ZomeApiResult<Address> {
+        class_admins::remove_class_admin(badge_class, admin)
+    }
+
+    #[zome_fn("hc_public")]
+    fn transfer_class_ownership(badge_class: Address, new_owner: Address) -> ZomeApiResult<Address> {
+        class_admins::transfer_class_ownership(badge_class, new_owner)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_allowed_issuers(badge_class: Address) -> ZomeApiResult<Vec<Address>> {
+        issuer_allowlist::get_allowed_issuers(&badge_class)
+    }
+
+    #[zome_fn("hc_public")]
+    fn add_allowed_issuer(badge_class: Address, issuer: Address) -> ZomeApiResult<Address> {
+        issuer_allowlist::add_allowed_issuer(badge_class, issuer)
+    }
+
+    #[zome_fn("hc_public")]
+    fn remove_allowed_issuer(badge_class: Address, issuer: Address) -> ZomeApiResult<Address> {
+        issuer_allowlist::remove_allowed_issuer(badge_class, issuer)
+    }
+
+    #[zome_fn("hc_public")]
+    fn create_tier_family(name: String, tiers: Vec<Address>) -> ZomeApiResult<Address> {
+        tier_family::create_tier_family(name, tiers)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_tier_families(badge_class: Address) -> ZomeApiResult<Vec<Address>> {
+        tier_family::get_tier_families(badge_class)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_agent_tier(agent_address: Address, tier_family: Address) -> ZomeApiResult<AgentTier> {
+        tier_family::get_agent_tier(agent_address, tier_family)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_pathway_eligibility(
+        agent_address: Address,
+        badge_class: Address,
+    ) -> ZomeApiResult<PathwayEligibility> {
+        pathway::get_pathway_eligibility(agent_address, badge_class)
+    }
+
+    #[zome_fn("hc_public")]
+    fn earn_pathway_badge(badge_class: Address) -> ZomeApiResult<Address> {
+        pathway::earn_pathway_badge(badge_class)
+    }
+
+    #[zome_fn("hc_public")]
+    fn apply_for_badge(
         badge_class: Address,
         evidences: Vec<Address>,
+        statement: String,
+    ) -> ZomeApiResult<Address> {
+        application::apply_for_badge(badge_class, evidences, statement)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_pending_applications(badge_class: Address) -> ZomeApiResult<Vec<ApplicationWithStatus>> {
+        application::get_pending_applications(badge_class)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_my_applications() -> ZomeApiResult<Vec<ApplicationWithStatus>> {
+        application::get_my_applications()
+    }
+
+    #[zome_fn("hc_public")]
+    fn endorse_application(
+        application: Address,
+        statement: String,
+        confidence: Confidence,
+        criteria: Option<Vec<String>>,
+    ) -> ZomeApiResult<Address> {
+        application::endorse_application(
+            application,
+            statement,
+            confidence,
+            criteria.unwrap_or_default(),
+        )
+    }
+
+    #[zome_fn("hc_public")]
+    fn reject_application(application: Address) -> ZomeApiResult<Address> {
+        application::reject_application(application)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_criteria_coverage(
+        recipient: Address,
+        badge_class: Address,
+    ) -> ZomeApiResult<Vec<CriterionCoverage>> {
+        criteria::get_criteria_coverage(recipient, badge_class)
+    }
+
+    #[zome_fn("hc_public")]
+    fn endorse(
+        target: Address,
+        target_type: EndorsementTargetType,
+        claim: String,
+    ) -> ZomeApiResult<Address> {
+        endorsement::endorse(target, target_type, claim)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_endorsements(target: Address) -> ZomeApiResult<Vec<Endorsement>> {
+        endorsement::get_endorsements(target)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_badge_classes_by_alignment(framework: String, code: String) -> ZomeApiResult<Vec<Address>> {
+        alignment::get_badge_classes_by_alignment(framework, code)
+    }
+
+    #[zome_fn("hc_public")]
+    fn export_open_badges_class(badge_class: Address) -> ZomeApiResult<OpenBadgeClass> {
+        open_badges::export_badge_class(badge_class)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_tags() -> ZomeApiResult<Vec<TagCount>> {
+        tag::get_tags()
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_badge_classes_by_tag(tag: String) -> ZomeApiResult<Vec<Address>> {
+        tag::get_badge_classes_by_tag(tag)
+    }
+
+    #[zome_fn("hc_public")]
+    fn search_badge_classes(query: String, limit: usize) -> ZomeApiResult<Vec<SearchResult>> {
+        search::search_badge_classes(query, limit)
+    }
+
+    #[zome_fn("hc_public")]
+    fn create_organization(name: String) -> ZomeApiResult<Address> {
+        organization::create_organization(name)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_organization(organization: Address) -> ZomeApiResult<Option<Organization>> {
+        organization::get_organization(&organization)
+    }
+
+    #[zome_fn("hc_public")]
+    fn set_organization_member(
+        organization: Address,
+        agent_address: Address,
+        role: OrganizationRole,
+    ) -> ZomeApiResult<Address> {
+        organization::set_organization_member(organization, agent_address, role)
+    }
+
+    #[zome_fn("hc_public")]
+    fn remove_organization_member(organization: Address, agent_address: Address) -> ZomeApiResult<Address> {
+        organization::remove_organization_member(organization, agent_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn delegate_issuing(
+        delegate: Address,
+        badge_classes: Vec<Address>,
+        expires_at: Iso8601,
+    ) -> ZomeApiResult<Address> {
+        delegation::delegate_issuing(delegate, badge_classes, expires_at)
+    }
+
+    #[zome_fn("hc_public")]
+    fn revoke_delegation(delegation_address: Address) -> ZomeApiResult<Address> {
+        delegation::revoke_delegation(delegation_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_delegations_to(delegate: Address) -> ZomeApiResult<Vec<Delegation>> {
+        delegation::get_delegations_to(delegate)
+    }
+
+    #[zome_fn("hc_public")]
+    fn attach_private_evidence(
+        badge_class: Address,
+        content: String,
+        salt: String,
     ) -> ZomeApiResult<Address> {
-        badge::claim_agent_deserves_badge(recipient, badge_class, evidences)
+        private_evidence::attach_private_evidence(badge_class, content, salt)
+    }
+
+    #[zome_fn("hc_public")]
+    fn request_private_evidence(commitment_address: Address) -> ZomeApiResult<()> {
+        private_evidence::request_private_evidence(commitment_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_evidence_commitments(badge_address: Address) -> ZomeApiResult<Vec<Address>> {
+        private_evidence::get_evidence_commitments(badge_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn decrypt_private_evidence(commitment_address: Address) -> ZomeApiResult<String> {
+        private_evidence::decrypt_private_evidence(commitment_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn disclose_private_evidence(commitment_address: Address) -> ZomeApiResult<EvidenceOpening> {
+        private_evidence::disclose_private_evidence(commitment_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn verify_evidence_disclosure(
+        commitment_address: Address,
+        opening: EvidenceOpening,
+    ) -> ZomeApiResult<bool> {
+        private_evidence::verify_evidence_disclosure(commitment_address, opening)
+    }
+
+    #[zome_fn("hc_public")]
+    fn set_badge_visibility(badge_address: Address, visibility: VisibilitySetting) -> ZomeApiResult<Address> {
+        visibility::set_badge_visibility(badge_address, visibility)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_badge_visibility(badge_address: Address) -> ZomeApiResult<Visibility> {
+        visibility::get_badge_visibility(badge_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn create_collection(name: String, badges: Vec<Address>) -> ZomeApiResult<Address> {
+        collection::create_collection(name, badges)
+    }
+
+    #[zome_fn("hc_public")]
+    fn reorder_collection(collection: Address, badges: Vec<Address>) -> ZomeApiResult<Address> {
+        collection::reorder_collection(collection, badges)
+    }
+
+    #[zome_fn("hc_public")]
+    fn share_collection(collection: Address, visibility: VisibilitySetting) -> ZomeApiResult<Address> {
+        collection::share_collection(collection, visibility)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_collections(agent_address: Address) -> ZomeApiResult<Vec<Address>> {
+        collection::get_collections(agent_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn export_open_badges_collection(collection: Address) -> ZomeApiResult<OpenBadgeCollection> {
+        open_badges::export_collection(collection)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_notifications() -> ZomeApiResult<Vec<NotificationWithAddress>> {
+        notification::get_notifications()
+    }
+
+    #[zome_fn("hc_public")]
+    fn mark_notification_read(notification: Address) -> ZomeApiResult<Address> {
+        notification::mark_notification_read(notification)
     }
 }
@@ -0,0 +1,282 @@
+use crate::badge::Badge;
+use crate::badge_class;
+use crate::class_admins;
+use crate::issuer_allowlist;
+use crate::organization::OrganizationRole;
+use hdk::holochain_core_types::time::Iso8601;
+use hdk::holochain_wasm_utils::api_serialization::get_links::GetLinksOptions;
+use hdk::prelude::*;
+use std::collections::HashMap;
+
+/**
+ * Node of the issuance provenance tree of a badge: the agent holding the badge,
+ * and the nodes for each of the issuers that claimed it for them
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct LineageNode {
+    pub agent: Address,
+    pub depth: usize,
+    pub issued_at: Option<Iso8601>,
+    pub is_creator: bool,
+    pub cyclic: bool,
+    pub issuers: Vec<LineageNode>,
+}
+
+/**
+ * Agent whose badge depends, directly or transitively, on the claims of another agent
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct BadgeDescendant {
+    pub agent: Address,
+    pub depth: usize,
+    pub issued_by: Address,
+    pub issued_at: Option<Iso8601>,
+}
+
+/** Handlers */
+
+/**
+ * Returns the issuance provenance tree of the badge of the recipient, following the claims made
+ * under every version of the class
+ */
+pub fn get_badge_lineage(recipient: Address, badge_class: Address) -> ZomeApiResult<LineageNode> {
+    let latest_version = badge_class::get_latest_version(&badge_class)?;
+
+    build_lineage_node(&latest_version, &recipient, 0, None, &mut vec![])
+}
+
+pub fn get_badge_descendants(
+    agent_address: Address,
+    badge_class: Address,
+) -> ZomeApiResult<Vec<BadgeDescendant>> {
+    let versions = badge_class::get_class_versions(&badge_class)?;
+    let mut descendants: Vec<BadgeDescendant> = vec![];
+    let mut visited: Vec<Address> = vec![agent_address.clone()];
+    let mut frontier: Vec<Address> = vec![agent_address];
+    let mut depth = 0;
+
+    while !frontier.is_empty() {
+        depth += 1;
+        let mut next_frontier: Vec<Address> = vec![];
+
+        for issuer in frontier {
+            for (badge_address, issued_at) in get_issued_badges(&issuer, &versions)? {
+                let badge: Badge = hdk::utils::get_as_type(badge_address)?;
+
+                if visited.contains(&badge.recipient) {
+                    continue;
+                }
+                visited.push(badge.recipient.clone());
+                next_frontier.push(badge.recipient.clone());
+
+                descendants.push(BadgeDescendant {
+                    agent: badge.recipient,
+                    depth,
+                    issued_by: issuer.clone(),
+                    issued_at,
+                });
+            }
+        }
+
+        frontier = next_frontier;
+    }
+
+    Ok(descendants)
+}
+
+/** Helpers */
+
+/**
+ * Builds the lineage node for the given holder, following its issuers until the class owner or administrators,
+ * cutting the branch if an agent appears twice in the same path.
+ * The badge class is the latest version, since holders may have earned their badge under any of them
+ */
+fn build_lineage_node(
+    badge_class: &Address,
+    holder: &Address,
+    depth: usize,
+    issued_at: Option<Iso8601>,
+    path: &mut Vec<Address>,
+) -> ZomeApiResult<LineageNode> {
+    let mut node = LineageNode {
+        agent: holder.clone(),
+        depth,
+        issued_at,
+        is_creator: class_admins::is_class_issuer(badge_class, holder)?,
+        cyclic: path.contains(holder),
+        issuers: vec![],
+    };
+
+    if node.is_creator || node.cyclic {
+        return Ok(node);
+    }
+
+    let (badge_address, badge) = match get_held_badge(holder, badge_class)? {
+        Some(held_badge) => held_badge,
+        None => return Ok(node),
+    };
+
+    path.push(holder.clone());
+    for issuer in badge.issuers {
+        let issued_at = get_claim_timestamp(&issuer, &badge_address, &badge.badge_class)?;
+        node.issuers.push(build_lineage_node(
+            badge_class,
+            &issuer,
+            depth + 1,
+            issued_at,
+            path,
+        )?);
+    }
+    path.pop();
+
+    Ok(node)
+}
+
+/**
+ * Returns the badges of the given versions of a class issued by the agent, with the time of the claim
+ */
+pub fn get_issued_badges(
+    issuer: &Address,
+    versions: &Vec<Address>,
+) -> ZomeApiResult<Vec<(Address, Option<Iso8601>)>> {
+    let mut issued_badges: Vec<(Address, Option<Iso8601>)> = vec![];
+
+    for version in versions {
+        let links = hdk::get_links_with_options(
+            issuer,
+            LinkMatch::Exactly("issuer->badge"),
+            LinkMatch::Exactly(String::from(version.clone()).as_str()),
+            GetLinksOptions {
+                headers: true,
+                ..Default::default()
+            },
+        )?;
+
+        for link in links.links() {
+            let issued_at = link
+                .headers
+                .first()
+                .map(|header| header.timestamp().clone());
+            issued_badges.push((link.address, issued_at));
+        }
+    }
+
+    Ok(issued_badges)
+}
+
+/**
+ * Returns the time at which the issuer claimed the given badge of the given version of a class, if found
+ */
+pub fn get_claim_timestamp(
+    issuer: &Address,
+    badge_address: &Address,
+    badge_class: &Address,
+) -> ZomeApiResult<Option<Iso8601>> {
+    let timestamp = get_issued_badges(issuer, &vec![badge_class.clone()])?
+        .into_iter()
+        .find(|(address, _)| address == badge_address)
+        .and_then(|(_, issued_at)| issued_at);
+
+    Ok(timestamp)
+}
+
+/**
+ * Returns the lineage the given issuer derives its badge from: the issuer itself if it is an administrator
+ * of the class or in its issuer allowlist, or got the badge directly from one of those,
+ * or its direct upstream issuers in whichever version of the class it holds the badge otherwise
+ */
+fn get_lineage_roots(badge_class: &Address, issuer: &Address) -> ZomeApiResult<Vec<Address>> {
+    let admins = class_admins::get_class_admins(badge_class)?;
+    let allowed_issuers = issuer_allowlist::get_allowed_issuers(badge_class)?;
+    let is_root = |agent: &Address| -> ZomeApiResult<bool> {
+        Ok(allowed_issuers.contains(agent) || admins.acts_for(agent, OrganizationRole::Issuer)?)
+    };
+
+    if is_root(issuer)? {
+        return Ok(vec![issuer.clone()]);
+    }
+
+    let badge = match get_held_badge(issuer, badge_class)? {
+        Some((_, badge)) => badge,
+        None => return Ok(vec![]),
+    };
+
+    for upstream in badge.issuers.iter() {
+        if is_root(upstream)? {
+            return Ok(vec![issuer.clone()]);
+        }
+    }
+
+    Ok(badge.issuers)
+}
+
+/**
+ * Returns the badge the agent holds under the latest version of the class it earned it in, with its initial address
+ */
+fn get_held_badge(
+    agent: &Address,
+    badge_class: &Address,
+) -> ZomeApiResult<Option<(Address, Badge)>> {
+    for version in badge_class::get_class_versions(badge_class)?.iter().rev() {
+        let badge_address = Badge::initial(agent, version).address()?;
+
+        if let Some(_) = hdk::get_entry(&badge_address)? {
+            let badge: Badge = hdk::utils::get_as_type(badge_address.clone())?;
+            return Ok(Some((badge_address, badge)));
+        }
+    }
+
+    Ok(None)
+}
+
+/**
+ * Returns the maximum number of issuers that can be assigned each to a different lineage
+ */
+pub fn count_independent_lineages(
+    badge_class: &Address,
+    issuers: &Vec<Address>,
+) -> ZomeApiResult<usize> {
+    let mut candidates: Vec<Vec<Address>> = vec![];
+    for issuer in issuers {
+        candidates.push(get_lineage_roots(badge_class, issuer)?);
+    }
+
+    let mut assignments: HashMap<Address, usize> = HashMap::new();
+    for issuer_index in 0..candidates.len() {
+        let mut visited: Vec<Address> = vec![];
+        assign_lineage(issuer_index, &candidates, &mut assignments, &mut visited);
+    }
+
+    Ok(assignments.len())
+}
+
+/**
+ * Tries to assign a lineage to the issuer, reassigning previously assigned issuers if needed
+ */
+fn assign_lineage(
+    issuer_index: usize,
+    candidates: &Vec<Vec<Address>>,
+    assignments: &mut HashMap<Address, usize>,
+    visited: &mut Vec<Address>,
+) -> bool {
+    for root in candidates[issuer_index].iter() {
+        if visited.contains(root) {
+            continue;
+        }
+        visited.push(root.clone());
+
+        let reassignable = match assignments.get(root).cloned() {
+            None => true,
+            Some(previous_issuer) => {
+                assign_lineage(previous_issuer, candidates, assignments, visited)
+            }
+        };
+
+        if reassignable {
+            assignments.insert(root.clone(), issuer_index);
+            return true;
+        }
+    }
+
+    false
+}
@@ -0,0 +1,319 @@
+use crate::attestation;
+use crate::badge::{self, Badge};
+use crate::class_admins;
+use crate::organization::{self, OrganizationRole};
+use crate::private_evidence::{self, EvidenceCommitment, EvidenceOpening};
+use hdk::holochain_core_types::time::Timeout;
+use hdk::prelude::*;
+use hdk::AGENT_ADDRESS;
+use std::convert::TryFrom;
+
+/**
+ * Milliseconds to wait for each receiver to acknowledge a notification: messages are sent while the
+ * claim is being made, so this is kept short for offline receivers not to delay it
+ */
+const NOTIFICATION_TIMEOUT: usize = 300;
+
+/**
+ * Direct messages sent between agents when something happens to the badges they are involved in:
+ * - badge-claimed: sent to the recipient when an issuer claims they deserve a badge
+ * - class-claimed: sent to the owner of a class when one of its badges is claimed
+ * - badge-completed: sent to the recipient when their badge gathers enough claims
+ * - private-evidence-shared: sent to the issuers of a class when the recipient of a badge attaches a private evidence
+ * - private-evidence-requested: sent by an issuer of a class to the recipient of a badge, who answers
+ *   with the opening of the private evidence instead of storing a notification
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum NotificationMessage {
+    BadgeClaimed {
+        badge: Address,
+        badge_class: Address,
+        issuer: Address,
+    },
+    ClassClaimed {
+        badge: Address,
+        badge_class: Address,
+        recipient: Address,
+        issuer: Address,
+    },
+    BadgeCompleted {
+        badge: Address,
+        badge_class: Address,
+    },
+    PrivateEvidenceShared {
+        commitment: Address,
+        evidence_commitment: EvidenceCommitment,
+        opening: EvidenceOpening,
+    },
+    PrivateEvidenceRequested {
+        commitment: Address,
+    },
+}
+
+/**
+ * Notification received by the agent and not read yet, kept only in its source chain
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct Notification {
+    pub sender: Address,
+    pub message: NotificationMessage,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NotificationWithAddress {
+    pub address: Address,
+    pub notification: Notification,
+}
+
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "notification",
+        description: "An unread notification received from another agent",
+        sharing: Sharing::Private,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: |validation_data: hdk::EntryValidationData<Notification>| {
+            match validation_data {
+                EntryValidationData::Create { .. } => Ok(()),
+                EntryValidationData::Delete { .. } => Ok(()),
+                _ => Err(String::from("Cannot update a notification")),
+            }
+        },
+        links: []
+    )
+}
+
+/** Handlers */
+
+/**
+ * Returns the unread notifications of the calling agent, oldest first.
+ *
+ * Notifications are authenticated here rather than when they are received, since the entries they refer to
+ * may not have reached the DHT by then: the ones that cannot be authenticated yet are left out
+ */
+pub fn get_notifications() -> ZomeApiResult<Vec<NotificationWithAddress>> {
+    let mut notifications: Vec<NotificationWithAddress> = vec![];
+
+    for address in hdk::query("notification".into(), 0, 0)? {
+        // Notifications that were read have been removed from the source chain
+        if let Some(Entry::App(_, entry_value)) = hdk::get_entry(&address)? {
+            let notification = Notification::try_from(entry_value).map_err(|_| {
+                ZomeApiError::from(String::from("Could not parse the notification"))
+            })?;
+
+            if assert_message_authentic(&notification.sender, &notification.message).is_ok() {
+                notifications.push(NotificationWithAddress {
+                    address,
+                    notification,
+                });
+            }
+        }
+    }
+
+    Ok(notifications)
+}
+
+pub fn mark_notification_read(notification_address: Address) -> ZomeApiResult<Address> {
+    if !hdk::query("notification".into(), 0, 0)?.contains(&notification_address) {
+        return Err(ZomeApiError::from(format!(
+            "Entry {} is not a notification of this agent",
+            notification_address
+        )));
+    }
+
+    hdk::remove_entry(&notification_address)
+}
+
+/** Helpers */
+
+/**
+ * Stores the notification sent by another agent, or answers its request for a private evidence,
+ * called from the receive callback of the zome.
+ *
+ * Only private evidences are checked on receipt, since they carry their commitment with them
+ */
+pub fn receive_notification(sender: Address, message_json: JsonString) -> String {
+    let message = match NotificationMessage::try_from(message_json) {
+        Ok(message) => message,
+        Err(_) => return String::from("Could not parse the notification"),
+    };
+
+    if let NotificationMessage::PrivateEvidenceRequested { commitment } = &message {
+        return match private_evidence::answer_request(&sender, commitment) {
+            Ok(opening) => JsonString::from(opening).to_string(),
+            Err(error) => error.to_string(),
+        };
+    }
+
+    let received = match &message {
+        NotificationMessage::PrivateEvidenceShared {
+            commitment,
+            evidence_commitment,
+            opening,
+        } => private_evidence::receive_opening(&sender, commitment, evidence_commitment, opening),
+        _ => Ok(()),
+    };
+
+    let result = received.and_then(|_| {
+        let notification = Notification { sender, message };
+
+        hdk::commit_entry(&Entry::App("notification".into(), notification.into()))
+    });
+
+    match result {
+        Ok(_) => String::from("ok"),
+        Err(error) => error.to_string(),
+    }
+}
+
+/**
+ * Sends the message to the given principal, or to the admins of the organization it refers to
+ *
+ * Notifications are best effort: agents that are offline simply miss them,
+ * and can still find their badges through the usual queries. Failed deliveries are logged
+ */
+pub fn notify(principal: &Address, message: NotificationMessage) -> ZomeApiResult<()> {
+    let receivers = match organization::get_organization(principal)? {
+        Some(organization) => organization.admins,
+        None => vec![principal.clone()],
+    };
+
+    for receiver in receivers {
+        if receiver == AGENT_ADDRESS.clone() {
+            continue;
+        }
+
+        let response = hdk::send(
+            receiver.clone(),
+            JsonString::from(message.clone()).to_string(),
+            Timeout::new(NOTIFICATION_TIMEOUT),
+        );
+
+        match response {
+            Ok(ref answer) if answer == "ok" => (),
+            Ok(answer) => hdk::debug(format!(
+                "Agent {} rejected the notification: {}",
+                receiver, answer
+            ))?,
+            Err(error) => hdk::debug(format!("Could not notify agent {}: {}", receiver, error))?,
+        }
+    }
+
+    Ok(())
+}
+
+/** Validation helpers */
+
+/**
+ * Asserts that the message refers to existing badges the sender is actually involved in,
+ * so that agents cannot fill the inbox of others with made up notifications
+ */
+fn assert_message_authentic(sender: &Address, message: &NotificationMessage) -> ZomeApiResult<()> {
+    match message {
+        NotificationMessage::BadgeClaimed { badge, issuer, .. } => {
+            let badge_entry: Badge = hdk::utils::get_as_type(badge.clone())?;
+
+            if badge_entry.recipient != AGENT_ADDRESS.clone() {
+                return Err(ZomeApiError::from(String::from(
+                    "The badge of the notification was not issued to this agent",
+                )));
+            }
+
+            assert_sender_issued(sender, badge, &badge_entry, issuer)
+        }
+        NotificationMessage::ClassClaimed {
+            badge,
+            badge_class,
+            issuer,
+            ..
+        } => {
+            let badge_entry: Badge = hdk::utils::get_as_type(badge.clone())?;
+
+            if badge_entry.badge_class != badge_class.clone()
+                || !class_admins::is_class_admin(badge_class, &AGENT_ADDRESS)?
+            {
+                return Err(ZomeApiError::from(String::from(
+                    "The badge of the notification does not belong to a class of this agent",
+                )));
+            }
+
+            assert_sender_issued(sender, badge, &badge_entry, issuer)
+        }
+        NotificationMessage::BadgeCompleted { badge, .. } => {
+            let badge_entry: Badge = hdk::utils::get_as_type(badge.clone())?;
+
+            if badge_entry.recipient != AGENT_ADDRESS.clone() {
+                return Err(ZomeApiError::from(String::from(
+                    "The badge of the notification was not issued to this agent",
+                )));
+            }
+
+            let mut sent_by_issuer = false;
+            for issuer in badge_entry.issuers.iter() {
+                if assert_sender_issued(sender, badge, &badge_entry, issuer).is_ok() {
+                    sent_by_issuer = true;
+                }
+            }
+
+            if !sent_by_issuer {
+                return Err(ZomeApiError::from(String::from(
+                    "Only the issuers of a badge can notify its completion",
+                )));
+            }
+
+            badge::assert_badge_completed(&badge_entry.badge_class, &badge_entry.recipient)
+        }
+        NotificationMessage::PrivateEvidenceShared {
+            commitment,
+            evidence_commitment,
+            opening,
+        } => private_evidence::assert_opening_authentic(
+            sender,
+            commitment,
+            evidence_commitment,
+            opening,
+        ),
+        NotificationMessage::PrivateEvidenceRequested { .. } => Err(ZomeApiError::from(
+            String::from("Requests for private evidences are answered instead of stored"),
+        )),
+    }
+}
+
+/**
+ * Asserts that the issuer is one of the issuers of the badge, and that the sender is that issuer,
+ * one of its members with the issuer role if it is an organization, or the delegate that claimed on its behalf
+ */
+fn assert_sender_issued(
+    sender: &Address,
+    badge_address: &Address,
+    badge: &Badge,
+    issuer: &Address,
+) -> ZomeApiResult<()> {
+    if !badge.issuers.contains(issuer) {
+        return Err(ZomeApiError::from(format!(
+            "Agent {} is not an issuer of the badge",
+            issuer
+        )));
+    }
+
+    if organization::acts_for(issuer, sender, OrganizationRole::Issuer)? {
+        return Ok(());
+    }
+
+    let delegated = attestation::get_badge_attestations(badge_address)?
+        .iter()
+        .any(|attestation| {
+            attestation.attestation.issuer == issuer.clone()
+                && attestation.attestation.delegate == Some(sender.clone())
+        });
+
+    match delegated {
+        true => Ok(()),
+        false => Err(ZomeApiError::from(format!(
+            "Agent {} did not claim the badge as {}",
+            sender, issuer
+        ))),
+    }
+}
@@ -0,0 +1,193 @@
+use crate::alignment::Alignment;
+use crate::badge::Badge;
+use crate::badge_class::BadgeClass;
+use crate::collection::{self, Collection};
+use crate::timestamp;
+use crate::visibility;
+use hdk::holochain_core_types::time::Iso8601;
+use hdk::prelude::*;
+
+const OPEN_BADGES_CONTEXT: &str = "https://w3id.org/openbadges/v2";
+
+/**
+ * Badge class serialized as an Open Badges 2.0 BadgeClass
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct OpenBadgeClass {
+    #[serde(rename = "@context")]
+    pub context: String,
+    #[serde(rename = "type")]
+    pub object_type: String,
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub image: String,
+    pub criteria: OpenBadgeCriteria,
+    pub issuer: String,
+    pub alignment: Vec<OpenBadgeAlignment>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenBadgeCriteria {
+    pub narrative: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenBadgeAlignment {
+    pub target_name: String,
+    pub target_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_framework: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_code: Option<String>,
+}
+
+impl From<Alignment> for OpenBadgeAlignment {
+    fn from(alignment: Alignment) -> OpenBadgeAlignment {
+        OpenBadgeAlignment {
+            target_name: alignment.target_name,
+            target_url: alignment.target_url,
+            target_description: alignment.target_description,
+            target_framework: alignment.target_framework,
+            target_code: alignment.target_code,
+        }
+    }
+}
+
+/**
+ * Badge serialized with the vocabulary of an Open Badges 2.0 Assertion, with its class embedded:
+ * badges live in the DHT instead of being hosted at an HTTP URL or signed, so the assertion carries
+ * no verification object and cannot be verified by Open Badges verifiers
+ */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenBadgeAssertion {
+    #[serde(rename = "type")]
+    pub object_type: String,
+    pub id: String,
+    pub recipient: OpenBadgeRecipient,
+    pub badge: OpenBadgeClass,
+    pub issued_on: Iso8601,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OpenBadgeRecipient {
+    #[serde(rename = "type")]
+    pub identity_type: String,
+    pub identity: String,
+    pub hashed: bool,
+}
+
+/**
+ * Collection serialized as a document listing the assertions of its badges in order,
+ * since the specification has no collection type of its own
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct OpenBadgeCollection {
+    #[serde(rename = "@context")]
+    pub context: String,
+    #[serde(rename = "type")]
+    pub object_type: String,
+    pub id: String,
+    pub name: String,
+    pub owner: String,
+    pub assertions: Vec<OpenBadgeAssertion>,
+}
+
+/** Handlers */
+
+pub fn export_badge_class(badge_class_address: Address) -> ZomeApiResult<OpenBadgeClass> {
+    let badge_class: BadgeClass = hdk::utils::get_as_type(badge_class_address.clone())?;
+
+    let narrative = match badge_class.criteria.is_empty() {
+        true => badge_class.description.clone(),
+        false => badge_class
+            .criteria
+            .iter()
+            .map(|criterion| format!("- {}", criterion.text))
+            .collect::<Vec<String>>()
+            .join("\n"),
+    };
+
+    Ok(OpenBadgeClass {
+        context: String::from(OPEN_BADGES_CONTEXT),
+        object_type: String::from("BadgeClass"),
+        id: iri(&badge_class_address),
+        name: badge_class.name,
+        description: badge_class.description,
+        image: badge_class.image,
+        criteria: OpenBadgeCriteria { narrative },
+        issuer: iri(&badge_class.creator_address),
+        alignment: badge_class
+            .alignments
+            .into_iter()
+            .map(OpenBadgeAlignment::from)
+            .collect(),
+    })
+}
+
+pub fn export_collection(collection_address: Address) -> ZomeApiResult<OpenBadgeCollection> {
+    let collection: Collection = hdk::utils::get_as_type(collection_address.clone())?;
+    let initial_address = collection::get_initial_address(&collection_address)?;
+
+    if !collection
+        .visibility
+        .allows(&initial_address, &collection.owner, &AGENT_ADDRESS)?
+    {
+        return Err(ZomeApiError::from(format!(
+            "Collection {} is not visible to this agent",
+            collection_address
+        )));
+    }
+
+    let assertions = visibility::filter_visible_badges(collection.badges)?
+        .iter()
+        .map(export_badge)
+        .collect::<ZomeApiResult<Vec<OpenBadgeAssertion>>>()?;
+
+    Ok(OpenBadgeCollection {
+        context: String::from(OPEN_BADGES_CONTEXT),
+        object_type: String::from("Collection"),
+        id: iri(&collection_address),
+        name: collection.name,
+        owner: iri(&collection.owner),
+        assertions,
+    })
+}
+
+/** Helpers */
+
+/**
+ * Exports the badge as an assertion issued when its latest version was committed,
+ * which is when its last claim was made or when it was earned for pathway badges
+ */
+fn export_badge(badge_address: &Address) -> ZomeApiResult<OpenBadgeAssertion> {
+    let badge: Badge = hdk::utils::get_as_type(badge_address.clone())?;
+    let initial_address = badge.initial_address()?;
+
+    let issued_on = timestamp::latest_entry_time(&initial_address)?.ok_or(ZomeApiError::from(
+        format!("Could not find when badge {} was issued", initial_address),
+    ))?;
+
+    Ok(OpenBadgeAssertion {
+        object_type: String::from("Assertion"),
+        id: iri(badge_address),
+        recipient: OpenBadgeRecipient {
+            identity_type: String::from("url"),
+            identity: iri(&badge.recipient),
+            hashed: false,
+        },
+        badge: export_badge_class(badge.badge_class)?,
+        issued_on,
+    })
+}
+
+/**
+ * Returns the IRI identifying the given entry or agent in Open Badges documents
+ */
+pub fn iri(address: &Address) -> String {
+    format!("urn:holochain:{}", address)
+}
@@ -0,0 +1,258 @@
+use crate::badge_class::BadgeClass;
+use crate::class_admins;
+use hdk::prelude::*;
+use hdk::AGENT_ADDRESS;
+use std::convert::TryFrom;
+
+/**
+ * Group of agents that can create and issue badge classes as a single creator,
+ * with admins managing its membership
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct Organization {
+    pub name: String,
+    pub creator_address: Address,
+    pub admins: Vec<Address>,
+    pub members: Vec<OrganizationMember>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OrganizationMember {
+    pub agent_address: Address,
+    pub role: OrganizationRole,
+}
+
+/**
+ * Admins can manage the organization and act for it in every way; issuers can only issue on its behalf
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OrganizationRole {
+    Member,
+    Issuer,
+    Admin,
+}
+
+impl Organization {
+    pub fn has_role(&self, agent_address: &Address, role: &OrganizationRole) -> bool {
+        if self.admins.contains(agent_address) {
+            return true;
+        }
+
+        self.members.iter().any(|member| {
+            member.agent_address == *agent_address
+                && (member.role == *role || *role == OrganizationRole::Member)
+        })
+    }
+}
+
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "organization",
+        description: "An organization that creates and issues badge classes through its members",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: |validation_data: hdk::EntryValidationData<Organization>| {
+            match validation_data {
+                EntryValidationData::Create { entry, validation_data } => {
+                    if !validation_data.sources().contains(&entry.creator_address) {
+                        return Err(String::from("The creator of an organization must sign its creation"));
+                    }
+
+                    if entry.admins != vec![entry.creator_address.clone()] || !entry.members.is_empty() {
+                        return Err(String::from("An organization must be created with its creator as the only admin"));
+                    }
+
+                    Ok(())
+                },
+                EntryValidationData::Modify {
+                    new_entry,
+                    old_entry,
+                    validation_data,
+                    ..
+                } => {
+                    if new_entry.creator_address != old_entry.creator_address {
+                        return Err(String::from("Cannot change the creator of an organization"));
+                    }
+
+                    let author = &validation_data.sources()[0];
+
+                    if !old_entry.admins.contains(author) {
+                        return Err(String::from("Only the admins of an organization can change it"));
+                    }
+
+                    if new_entry.admins.is_empty() {
+                        return Err(String::from("An organization must have at least one admin"));
+                    }
+
+                    Ok(())
+                },
+                _ => Err(String::from("Cannot delete an organization")),
+            }
+        },
+        links: [
+            to!(
+                "badge_class",
+                link_type: "organization->badge_class",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, validation_data } => {
+                            if !signed_by(link.link.base(), &validation_data.sources(), OrganizationRole::Admin)? {
+                                return Err(String::from("Only the admins of an organization can link its badge classes"));
+                            }
+
+                            let badge_class: BadgeClass = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            if badge_class.creator_address == link.link.base().clone() {
+                                return Ok(());
+                            }
+
+                            match class_admins::get_class_owner(link.link.target())? == link.link.base().clone() {
+                                true => Ok(()),
+                                false => Err(String::from("Cannot link \"organization->badge_class\" to a badge class that is not owned by the organization"))
+                            }
+                        },
+                        hdk::LinkValidationData::LinkRemove { link, validation_data } => {
+                            class_admins::assert_owner_link_removable(link.link.base(), link.link.target(), &validation_data.sources())?;
+
+                            Ok(())
+                        }
+                    }
+                }
+            )
+        ]
+    )
+}
+
+/** Handlers */
+
+pub fn create_organization(name: String) -> ZomeApiResult<Address> {
+    let organization = Organization {
+        name,
+        creator_address: AGENT_ADDRESS.clone(),
+        admins: vec![AGENT_ADDRESS.clone()],
+        members: vec![],
+    };
+
+    let organization_entry = Entry::App("organization".into(), organization.into());
+    hdk::commit_entry(&organization_entry)
+}
+
+/**
+ * Adds the agent to the organization with the given role, or changes its role if it was already a member
+ */
+pub fn set_organization_member(
+    organization_address: Address,
+    agent_address: Address,
+    role: OrganizationRole,
+) -> ZomeApiResult<Address> {
+    update_organization(&organization_address, |organization| {
+        organization.admins.retain(|admin| admin.clone() != agent_address);
+        organization
+            .members
+            .retain(|member| member.agent_address != agent_address);
+
+        match role {
+            OrganizationRole::Admin => organization.admins.push(agent_address.clone()),
+            _ => organization.members.push(OrganizationMember {
+                agent_address: agent_address.clone(),
+                role: role.clone(),
+            }),
+        }
+
+        Ok(())
+    })
+}
+
+pub fn remove_organization_member(
+    organization_address: Address,
+    agent_address: Address,
+) -> ZomeApiResult<Address> {
+    update_organization(&organization_address, |organization| {
+        if !organization.has_role(&agent_address, &OrganizationRole::Member) {
+            return Err(ZomeApiError::from(format!(
+                "Agent {} is not a member of the organization",
+                agent_address
+            )));
+        }
+
+        organization.admins.retain(|admin| admin.clone() != agent_address);
+        organization
+            .members
+            .retain(|member| member.agent_address != agent_address);
+
+        Ok(())
+    })
+}
+
+/** Helpers */
+
+/**
+ * Returns the organization at the given address, or None if the address is not an organization
+ */
+pub fn get_organization(address: &Address) -> ZomeApiResult<Option<Organization>> {
+    match hdk::get_entry(address)? {
+        Some(Entry::App(entry_type, entry_value)) => {
+            if entry_type.to_string() != "organization" {
+                return Ok(None);
+            }
+
+            let organization = Organization::try_from(entry_value)
+                .map_err(|_| ZomeApiError::from(String::from("Could not parse the organization")))?;
+
+            Ok(Some(organization))
+        }
+        _ => Ok(None),
+    }
+}
+
+/**
+ * Returns whether the agent can act as the given principal with the given role:
+ * either the principal is the agent itself, or it is an organization in which the agent has that role
+ */
+pub fn acts_for(principal: &Address, agent_address: &Address, role: OrganizationRole) -> ZomeApiResult<bool> {
+    if principal == agent_address {
+        return Ok(true);
+    }
+
+    match get_organization(principal)? {
+        Some(organization) => Ok(organization.has_role(agent_address, &role)),
+        None => Ok(false),
+    }
+}
+
+/**
+ * Returns whether any of the signers of an entry can act as the given principal with the given role
+ */
+pub fn signed_by(principal: &Address, sources: &Vec<Address>, role: OrganizationRole) -> ZomeApiResult<bool> {
+    for source in sources {
+        if acts_for(principal, source, role.clone())? {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+fn update_organization<F>(organization_address: &Address, modify: F) -> ZomeApiResult<Address>
+where
+    F: FnOnce(&mut Organization) -> ZomeApiResult<()>,
+{
+    let mut organization: Organization = hdk::utils::get_as_type(organization_address.clone())?;
+
+    if !organization.admins.contains(&AGENT_ADDRESS) {
+        return Err(ZomeApiError::from(String::from(
+            "Only the admins of an organization can change it",
+        )));
+    }
+
+    modify(&mut organization)?;
+
+    let new_entry = Entry::App("organization".into(), organization.into());
+    hdk::update_entry(new_entry, organization_address)
+}
@@ -0,0 +1,86 @@
+use crate::badge::{self, Badge};
+use crate::badge_class::{BadgeClass, BadgeClassKind};
+use hdk::prelude::*;
+use hdk::AGENT_ADDRESS;
+
+/**
+ * Whether an agent can earn a pathway badge, with the components they still have to complete
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct PathwayEligibility {
+    pub badge_class: Address,
+    pub eligible: bool,
+    pub missing_components: Vec<Address>,
+}
+
+/** Handlers */
+
+pub fn get_pathway_eligibility(
+    agent_address: Address,
+    badge_class: Address,
+) -> ZomeApiResult<PathwayEligibility> {
+    let components = get_components(&badge_class)?;
+
+    let missing_components: Vec<Address> = components
+        .into_iter()
+        .filter(|component| badge::assert_holder_valid(component, &agent_address).is_err())
+        .collect();
+
+    Ok(PathwayEligibility {
+        badge_class,
+        eligible: missing_components.is_empty(),
+        missing_components,
+    })
+}
+
+pub fn earn_pathway_badge(badge_class: Address) -> ZomeApiResult<Address> {
+    let eligibility = get_pathway_eligibility(AGENT_ADDRESS.clone(), badge_class.clone())?;
+
+    if !eligibility.eligible {
+        return Err(ZomeApiError::from(format!(
+            "Agent is missing the components {:?} of the pathway",
+            eligibility.missing_components
+        )));
+    }
+
+    let badge = Badge::initial(&AGENT_ADDRESS, &badge_class);
+    let badge_entry = Entry::App("badge".into(), badge.into());
+    let badge_address = hdk::commit_entry(&badge_entry)?;
+
+    hdk::link_entries(&AGENT_ADDRESS, &badge_address, "recipient->badge", "completed")?;
+    hdk::link_entries(&badge_class, &badge_address, "badge_class->badge", "")?;
+
+    Ok(badge_address)
+}
+
+/** Helpers */
+
+fn get_components(badge_class: &Address) -> ZomeApiResult<Vec<Address>> {
+    let class: BadgeClass = hdk::utils::get_as_type(badge_class.clone())?;
+
+    match class.kind {
+        BadgeClassKind::Pathway { components } => Ok(components),
+        BadgeClassKind::Claims => Err(ZomeApiError::from(format!(
+            "Badge class {} is not a pathway",
+            class.name
+        ))),
+    }
+}
+
+/** Validation helpers */
+
+/**
+ * Pathway badges can only be earned by agents holding completed badges of all the components
+ */
+pub fn assert_components_held(components: &Vec<Address>, recipient: &Address) -> ZomeApiResult<()> {
+    for component in components {
+        if badge::assert_holder_valid(component, recipient).is_err() {
+            return Err(ZomeApiError::from(format!(
+                "Agent {} does not hold the pathway component {}",
+                recipient, component
+            )));
+        }
+    }
+
+    Ok(())
+}
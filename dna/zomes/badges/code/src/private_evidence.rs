@@ -0,0 +1,467 @@
+use crate::badge::{self, Badge};
+use crate::badge_class;
+use crate::class_admins;
+use crate::issuer_allowlist;
+use crate::notification::{self, NotificationMessage};
+use hdk::holochain_core_types::time::Timeout;
+use hdk::prelude::*;
+use hdk::AGENT_ADDRESS;
+use std::convert::TryFrom;
+
+/** Minimum length of the random salt that hides the content of a private evidence */
+const MIN_SALT_LENGTH: usize = 32;
+
+/**
+ * Milliseconds to wait for the recipient of a badge to answer a request for one of its private evidences:
+ * unlike notifications, the issuer is waiting for the answer
+ */
+const REQUEST_TIMEOUT: usize = 5000;
+
+/**
+ * Evidence whose content is only kept in the source chains of the badge recipient and its issuers,
+ * each of them encrypting it with their own key.
+ *
+ * The recipient sends the opening through direct messages to the owner, administrators and allowed issuers
+ * of the class when attaching the evidence, and to any other issuer of the class that requests it later.
+ * The issuers check it against the commitment before storing it
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct PrivateEvidence {
+    pub commitment: Address,
+    pub encrypted_opening: String,
+}
+
+/**
+ * Content of a private evidence together with the salt that hides it: its hash is the published commitment,
+ * and disclosing it lets anyone check it against the commitment
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone, PartialEq)]
+pub struct EvidenceOpening {
+    pub badge: Address,
+    pub content: String,
+    pub salt: String,
+}
+
+impl EvidenceOpening {
+    pub fn commitment(&self) -> ZomeApiResult<Address> {
+        let entry = Entry::App("evidence_opening".into(), self.clone().into());
+
+        hdk::entry_address(&entry)
+    }
+}
+
+/**
+ * Public commitment to a private evidence of a badge, published by its recipient
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone, PartialEq)]
+pub struct EvidenceCommitment {
+    pub badge: Address,
+    pub badge_class: Address,
+    pub owner: Address,
+    pub commitment: Address,
+}
+
+pub fn private_entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "private_evidence",
+        description: "The encrypted content of a private evidence, kept only in the source chains of its recipient and issuers",
+        sharing: Sharing::Private,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: |validation_data: hdk::EntryValidationData<PrivateEvidence>| {
+            match validation_data {
+                EntryValidationData::Create { .. } => Ok(()),
+                _ => Err(String::from("Cannot update or delete a private evidence")),
+            }
+        },
+        links: []
+    )
+}
+
+/**
+ * Openings travel in direct messages between the recipient and the issuers only: issuers hash the opening
+ * they receive as this entry type and compare it with the published commitment of the evidence
+ */
+pub fn opening_entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "evidence_opening",
+        description: "The plain content of a private evidence with its salt",
+        sharing: Sharing::Private,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: |_validation_data: hdk::EntryValidationData<EvidenceOpening>| {
+            Err(String::from("Evidence openings cannot be committed"))
+        },
+        links: []
+    )
+}
+
+pub fn commitment_entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "evidence_commitment",
+        description: "The hash commitment to a private evidence of a badge",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: |validation_data: hdk::EntryValidationData<EvidenceCommitment>| {
+            match validation_data {
+                EntryValidationData::Create { entry, validation_data } => {
+                    if !validation_data.sources().contains(&entry.owner) {
+                        return Err(String::from("Only the owner of a private evidence can publish its commitment"));
+                    }
+
+                    match Badge::initial(&entry.owner, &entry.badge_class).address()? == entry.badge {
+                        true => Ok(()),
+                        false => Err(String::from("Only the recipient of a badge can attach private evidences to it"))
+                    }
+                },
+                _ => Err(String::from("Cannot update or delete an evidence commitment")),
+            }
+        },
+        links: [
+            from!(
+                "badge",
+                link_type: "badge->evidence_commitment",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, .. } => {
+                            let commitment: EvidenceCommitment = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            match commitment.badge == link.link.base().clone() {
+                                true => Ok(()),
+                                false => Err(String::from("Cannot link \"badge->evidence_commitment\" to a commitment for another badge"))
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            )
+        ]
+    )
+}
+
+/** Handlers */
+
+/**
+ * Keeps the content encrypted in the source chain of the calling agent, publishes only its commitment
+ * as an evidence of its badge of the given class, and sends the content to the issuers of the class.
+ * Evidences can be attached before the first claim, for issuers to check them before claiming.
+ *
+ * The salt must be generated by the caller with a cryptographically secure random generator,
+ * since the zome has no source of randomness
+ */
+pub fn attach_private_evidence(
+    badge_class: Address,
+    content: String,
+    salt: String,
+) -> ZomeApiResult<Address> {
+    let badge_class = badge_class::get_latest_version(&badge_class)?;
+
+    if salt.chars().count() < MIN_SALT_LENGTH {
+        return Err(ZomeApiError::from(format!(
+            "The salt of a private evidence must have at least {} characters",
+            MIN_SALT_LENGTH
+        )));
+    }
+
+    let badge_entry = Entry::App(
+        "badge".into(),
+        Badge::initial(&AGENT_ADDRESS, &badge_class).into(),
+    );
+    let badge_address = hdk::entry_address(&badge_entry)?;
+
+    if let None = hdk::get_entry(&badge_address)? {
+        hdk::commit_entry(&badge_entry)?;
+    }
+
+    let opening = EvidenceOpening {
+        badge: badge_address.clone(),
+        content,
+        salt,
+    };
+    let commitment = opening.commitment()?;
+
+    store_opening(&commitment, &opening)?;
+
+    let evidence_commitment = EvidenceCommitment {
+        badge: badge_address.clone(),
+        badge_class: badge_class.clone(),
+        owner: AGENT_ADDRESS.clone(),
+        commitment,
+    };
+    let commitment_address = hdk::commit_entry(&Entry::App(
+        "evidence_commitment".into(),
+        evidence_commitment.clone().into(),
+    ))?;
+
+    hdk::link_entries(
+        &badge_address,
+        &commitment_address,
+        "badge->evidence_commitment",
+        "",
+    )?;
+
+    for issuer in get_class_issuers(&badge_address, &badge_class)? {
+        notification::notify(
+            &issuer,
+            NotificationMessage::PrivateEvidenceShared {
+                commitment: commitment_address.clone(),
+                evidence_commitment: evidence_commitment.clone(),
+                opening: opening.clone(),
+            },
+        )?;
+    }
+
+    Ok(commitment_address)
+}
+
+/**
+ * Asks the recipient of a badge for one of its private evidences, for the issuers of its class
+ * that were not sent it when it was attached
+ */
+pub fn request_private_evidence(commitment_address: Address) -> ZomeApiResult<()> {
+    let evidence_commitment: EvidenceCommitment =
+        hdk::utils::get_as_type(commitment_address.clone())?;
+
+    badge::assert_issuer_valid(&evidence_commitment.badge_class, &AGENT_ADDRESS)?;
+
+    let response = hdk::send(
+        evidence_commitment.owner.clone(),
+        JsonString::from(NotificationMessage::PrivateEvidenceRequested {
+            commitment: commitment_address.clone(),
+        })
+        .to_string(),
+        Timeout::new(REQUEST_TIMEOUT),
+    )?;
+
+    let opening = EvidenceOpening::try_from(JsonString::from_json(&response)).map_err(|_| {
+        ZomeApiError::from(format!(
+            "The owner of the private evidence did not share it: {}",
+            response
+        ))
+    })?;
+
+    if !verify_evidence_disclosure(commitment_address, opening.clone())? {
+        return Err(ZomeApiError::from(String::from(
+            "The private evidence does not match its commitment",
+        )));
+    }
+
+    store_opening(&evidence_commitment.commitment, &opening)
+}
+
+pub fn get_evidence_commitments(badge_address: Address) -> ZomeApiResult<Vec<Address>> {
+    let links = hdk::get_links(
+        &badge_address,
+        LinkMatch::Exactly("badge->evidence_commitment"),
+        LinkMatch::Any,
+    )?;
+
+    Ok(links.addresses())
+}
+
+/**
+ * Returns the content of one of the private evidences that the calling agent attached or received as issuer
+ */
+pub fn decrypt_private_evidence(commitment_address: Address) -> ZomeApiResult<String> {
+    Ok(get_opening(&commitment_address)?.content)
+}
+
+/**
+ * Returns the opening of one of the private evidences of the calling agent,
+ * to be handed to a verifier that will check it against the published commitment
+ */
+pub fn disclose_private_evidence(commitment_address: Address) -> ZomeApiResult<EvidenceOpening> {
+    get_opening(&commitment_address)
+}
+
+/**
+ * Returns whether the disclosed opening matches the published commitment
+ */
+pub fn verify_evidence_disclosure(
+    commitment_address: Address,
+    opening: EvidenceOpening,
+) -> ZomeApiResult<bool> {
+    let evidence_commitment: EvidenceCommitment = hdk::utils::get_as_type(commitment_address)?;
+
+    Ok(evidence_commitment.badge == opening.badge
+        && evidence_commitment.commitment == opening.commitment()?)
+}
+
+/** Helpers */
+
+/**
+ * Stores the opening that the recipient of a badge sent to the calling agent as an issuer of its class,
+ * after checking it against the commitment sent with it
+ */
+pub fn receive_opening(
+    sender: &Address,
+    commitment_address: &Address,
+    evidence_commitment: &EvidenceCommitment,
+    opening: &EvidenceOpening,
+) -> ZomeApiResult<()> {
+    assert_opening_authentic(sender, commitment_address, evidence_commitment, opening)?;
+
+    if badge::assert_issuer_valid(&evidence_commitment.badge_class, &AGENT_ADDRESS).is_err() {
+        return Err(ZomeApiError::from(String::from(
+            "Private evidences are only shared with the issuers of the badge class",
+        )));
+    }
+
+    store_opening(&evidence_commitment.commitment, opening)
+}
+
+/**
+ * Returns the opening of a private evidence of the calling agent to an issuer of the class that requested it
+ */
+pub fn answer_request(
+    sender: &Address,
+    commitment_address: &Address,
+) -> ZomeApiResult<EvidenceOpening> {
+    let evidence_commitment: EvidenceCommitment =
+        hdk::utils::get_as_type(commitment_address.clone())?;
+
+    if evidence_commitment.owner != AGENT_ADDRESS.clone() {
+        return Err(ZomeApiError::from(String::from(
+            "This agent does not own the requested private evidence",
+        )));
+    }
+
+    if badge::assert_issuer_valid(&evidence_commitment.badge_class, sender).is_err() {
+        return Err(ZomeApiError::from(String::from(
+            "Private evidences are only shared with the issuers of the badge class",
+        )));
+    }
+
+    get_opening(commitment_address)
+}
+
+/**
+ * Returns the owner, administrators and allowed issuers of the class, and the issuers that already
+ * claimed the badge if it exists
+ */
+fn get_class_issuers(
+    badge_address: &Address,
+    badge_class: &Address,
+) -> ZomeApiResult<Vec<Address>> {
+    let admins = class_admins::get_class_admins(badge_class)?;
+
+    let mut candidates = vec![admins.owner];
+    candidates.extend(admins.admins);
+    candidates.extend(issuer_allowlist::get_allowed_issuers(badge_class)?);
+
+    if let Some(_) = hdk::get_entry(badge_address)? {
+        let badge: Badge = hdk::utils::get_as_type(badge_address.clone())?;
+        candidates.extend(badge.issuers);
+    }
+
+    let mut issuers: Vec<Address> = vec![];
+    for candidate in candidates {
+        if !issuers.contains(&candidate) {
+            issuers.push(candidate);
+        }
+    }
+
+    Ok(issuers)
+}
+
+/**
+ * Commits the opening encrypted with the key of the calling agent, unless it already holds it
+ */
+fn store_opening(commitment: &Address, opening: &EvidenceOpening) -> ZomeApiResult<()> {
+    if let Some(_) = find_private_evidence(commitment)? {
+        return Ok(());
+    }
+
+    let private_evidence = PrivateEvidence {
+        commitment: commitment.clone(),
+        encrypted_opening: hdk::encrypt(JsonString::from(opening.clone()).to_string())?,
+    };
+    hdk::commit_entry(&Entry::App(
+        "private_evidence".into(),
+        private_evidence.into(),
+    ))?;
+
+    Ok(())
+}
+
+fn find_private_evidence(commitment: &Address) -> ZomeApiResult<Option<PrivateEvidence>> {
+    for address in hdk::query("private_evidence".into(), 0, 0)? {
+        let private_evidence: PrivateEvidence = hdk::utils::get_as_type(address)?;
+
+        if private_evidence.commitment == commitment.clone() {
+            return Ok(Some(private_evidence));
+        }
+    }
+
+    Ok(None)
+}
+
+/**
+ * Finds the private evidence for the given commitment in the source chain of the calling agent, and decrypts it
+ */
+fn get_opening(commitment_address: &Address) -> ZomeApiResult<EvidenceOpening> {
+    let evidence_commitment: EvidenceCommitment =
+        hdk::utils::get_as_type(commitment_address.clone())?;
+
+    match find_private_evidence(&evidence_commitment.commitment)? {
+        Some(private_evidence) => {
+            let opening_json = hdk::decrypt(private_evidence.encrypted_opening)?;
+
+            EvidenceOpening::try_from(JsonString::from_json(&opening_json)).map_err(|_| {
+                ZomeApiError::from(String::from("Could not parse the private evidence"))
+            })
+        }
+        None => Err(ZomeApiError::from(String::from(
+            "This agent does not hold the private evidence for the given commitment",
+        ))),
+    }
+}
+
+/** Validation helpers */
+
+/**
+ * Asserts that the commitment sent with the opening is the one the sender published for its own badge,
+ * and that the opening matches it, without fetching the commitment since it may not have reached the DHT yet
+ */
+pub fn assert_opening_authentic(
+    sender: &Address,
+    commitment_address: &Address,
+    evidence_commitment: &EvidenceCommitment,
+    opening: &EvidenceOpening,
+) -> ZomeApiResult<()> {
+    let commitment_entry = Entry::App(
+        "evidence_commitment".into(),
+        evidence_commitment.clone().into(),
+    );
+    if hdk::entry_address(&commitment_entry)? != commitment_address.clone() {
+        return Err(ZomeApiError::from(String::from(
+            "The commitment does not match its address",
+        )));
+    }
+
+    if evidence_commitment.owner != sender.clone()
+        || Badge::initial(sender, &evidence_commitment.badge_class).address()?
+            != evidence_commitment.badge
+    {
+        return Err(ZomeApiError::from(String::from(
+            "Only the owner of a private evidence can share it",
+        )));
+    }
+
+    if opening.badge != evidence_commitment.badge
+        || opening.commitment()? != evidence_commitment.commitment
+    {
+        return Err(ZomeApiError::from(String::from(
+            "The private evidence does not match its commitment",
+        )));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use crate::anchor;
+use crate::badge_class::{self, BadgeClass};
+use hdk::prelude::*;
+
+/** Tokens shorter than this are too common to be worth indexing */
+const MIN_TOKEN_LENGTH: usize = 3;
+
+/**
+ * Badge class matching a search query, with the number of query terms it matched
+ */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SearchResult {
+    pub badge_class: Address,
+    pub matched_terms: usize,
+}
+
+/** Handlers */
+
+/**
+ * Returns the latest versions of the badge classes whose name or description contain any term of the query,
+ * the ones matching most terms first. Classes only match the terms that their latest version still contains
+ */
+pub fn search_badge_classes(query: String, limit: usize) -> ZomeApiResult<Vec<SearchResult>> {
+    let mut results: Vec<SearchResult> = vec![];
+
+    for token in tokenize(&query) {
+        let links = hdk::get_links(
+            &anchors::anchor_address::<anchor::BadgeAnchors>(anchor::SEARCH_ANCHOR_TYPE, &token)?,
+            LinkMatch::Exactly("search->badge_class"),
+            LinkMatch::Any,
+        )?;
+
+        let mut matched: Vec<Address> = vec![];
+        for address in links.addresses() {
+            let latest_version = badge_class::get_latest_version(&address)?;
+            let latest_class: BadgeClass = hdk::utils::get_as_type(latest_version.clone())?;
+
+            let still_matching = badge_class_tokens(&latest_class).contains(&token);
+
+            if still_matching && !matched.contains(&latest_version) {
+                matched.push(latest_version);
+            }
+        }
+
+        for badge_class in matched {
+            match results
+                .iter_mut()
+                .find(|result| result.badge_class == badge_class)
+            {
+                Some(result) => result.matched_terms += 1,
+                None => results.push(SearchResult {
+                    badge_class,
+                    matched_terms: 1,
+                }),
+            }
+        }
+    }
+
+    results.sort_by(|a, b| b.matched_terms.cmp(&a.matched_terms));
+
+    results.truncate(limit);
+
+    Ok(results)
+}
+
+/** Helpers */
+
+/**
+ * Links the badge class from the anchors of all the tokens in its name and description
+ */
+pub fn index_badge_class(class_address: &Address, class: &BadgeClass) -> ZomeApiResult<()> {
+    for token in badge_class_tokens(class) {
+        hdk::link_entries(
+            &anchors::anchor::<anchor::BadgeAnchors>(anchor::SEARCH_ANCHOR_TYPE, &token)?,
+            class_address,
+            "search->badge_class",
+            "",
+        )?;
+    }
+
+    Ok(())
+}
+
+pub fn badge_class_tokens(class: &BadgeClass) -> Vec<String> {
+    tokenize(&format!("{} {}", class.name, class.description))
+}
+
+/**
+ * Splits the text in unique lowercase alphanumeric tokens, skipping the shortest ones
+ */
+pub fn tokenize(text: &String) -> Vec<String> {
+    let mut tokens: Vec<String> = vec![];
+
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        let token = word.to_lowercase();
+
+        if token.chars().count() >= MIN_TOKEN_LENGTH && !tokens.contains(&token) {
+            tokens.push(token);
+        }
+    }
+
+    tokens
+}
@@ -0,0 +1,110 @@
+use crate::anchor;
+use crate::badge_class::{self, BadgeClass};
+use anchors::Anchor;
+use hdk::prelude::*;
+
+/**
+ * Tag attached to at least one badge class, with the number of classes tagged with it
+ */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+/** Handlers */
+
+pub fn get_tags() -> ZomeApiResult<Vec<TagCount>> {
+    let mut tags: Vec<TagCount> = vec![];
+
+    for address in anchors::list_anchors::<anchor::BadgeAnchors>(anchor::TAG_ANCHOR_TYPE)? {
+        let tag_anchor: Anchor = hdk::utils::get_as_type(address)?;
+        let tag = tag_anchor.anchor_text.unwrap_or_default();
+
+        let count = get_badge_classes_by_tag(tag.clone())?.len();
+        if count > 0 {
+            tags.push(TagCount { tag, count });
+        }
+    }
+
+    tags.sort_by(|a, b| b.count.cmp(&a.count).then(a.tag.cmp(&b.tag)));
+
+    Ok(tags)
+}
+
+/**
+ * Returns the latest versions of the badge classes tagged with the given tag, leaving out the classes
+ * whose latest version dropped it
+ */
+pub fn get_badge_classes_by_tag(tag: String) -> ZomeApiResult<Vec<Address>> {
+    let tag = normalize_tag(&tag);
+    let links = hdk::get_links(
+        &anchors::anchor_address::<anchor::BadgeAnchors>(anchor::TAG_ANCHOR_TYPE, &tag)?,
+        LinkMatch::Exactly("tag->badge_class"),
+        LinkMatch::Any,
+    )?;
+
+    let mut badge_classes: Vec<Address> = vec![];
+    for address in links.addresses() {
+        let latest_version = badge_class::get_latest_version(&address)?;
+        let latest_class: BadgeClass = hdk::utils::get_as_type(latest_version.clone())?;
+
+        let still_tagged = latest_class
+            .tags
+            .iter()
+            .any(|class_tag| normalize_tag(class_tag) == tag);
+
+        if still_tagged && !badge_classes.contains(&latest_version) {
+            badge_classes.push(latest_version);
+        }
+    }
+
+    Ok(badge_classes)
+}
+
+/** Helpers */
+
+/**
+ * Tags are compared case insensitively and without surrounding whitespace
+ */
+pub fn normalize_tag(tag: &String) -> String {
+    tag.trim().to_lowercase()
+}
+
+/**
+ * Links the badge class from the anchors of all its tags
+ */
+pub fn index_tags(class_address: &Address, class: &BadgeClass) -> ZomeApiResult<()> {
+    for tag in class.tags.iter() {
+        hdk::link_entries(
+            &anchors::anchor::<anchor::BadgeAnchors>(anchor::TAG_ANCHOR_TYPE, tag)?,
+            class_address,
+            "tag->badge_class",
+            "",
+        )?;
+    }
+
+    Ok(())
+}
+
+/** Validation helpers */
+
+pub fn assert_tags_valid(tags: &Vec<String>) -> ZomeApiResult<()> {
+    for (index, tag) in tags.iter().enumerate() {
+        if tag.is_empty() || normalize_tag(tag) != tag.clone() {
+            return Err(ZomeApiError::from(format!(
+                "Tag \"{}\" must be non empty, lowercase and without surrounding whitespace",
+                tag
+            )));
+        }
+
+        if tags[..index].contains(tag) {
+            return Err(ZomeApiError::from(format!(
+                "Tag \"{}\" can only appear once in a badge class",
+                tag
+            )));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,219 @@
+use crate::badge::{self, Badge};
+use crate::badge_class::{self, BadgeClass};
+use crate::class_admins;
+use hdk::prelude::*;
+use hdk::AGENT_ADDRESS;
+
+/**
+ * Ordered list of badge classes representing progression levels of the same skill,
+ * where each tier can only be claimed for agents holding the previous one
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct TierFamily {
+    pub name: String,
+    pub creator_address: Address,
+    pub tiers: Vec<Address>,
+}
+
+/**
+ * Current tier of an agent in a tier family, and the requirements to reach the next one
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct AgentTier {
+    pub tier_family: Address,
+    pub current_tier: Option<usize>,
+    pub current_badge_class: Option<Address>,
+    pub next_tier: Option<TierRequirements>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TierRequirements {
+    pub tier: usize,
+    pub badge_class: Address,
+    pub validators: usize,
+    pub min_lineages: Option<usize>,
+    pub issuers: Vec<Address>,
+}
+
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "tier_family",
+        description: "Ordered list of badge classes that must be earned in order",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: |validation_data: hdk::EntryValidationData<TierFamily>| {
+            match validation_data {
+                EntryValidationData::Create { entry, validation_data } => {
+                    if entry.tiers.len() < 2 {
+                        return Err(String::from("A tier family must have at least two tiers"));
+                    }
+
+                    for (index, tier) in entry.tiers.iter().enumerate() {
+                        if entry.tiers[..index].contains(tier) {
+                            return Err(String::from("A badge class can only appear once in a tier family"));
+                        }
+                    }
+
+                    if !validation_data.sources().contains(&entry.creator_address) {
+                        return Err(String::from("The creator of a tier family must sign its creation"));
+                    }
+
+                    for tier in entry.tiers.iter() {
+                        if !class_admins::is_class_admin(tier, &entry.creator_address)? {
+                            return Err(String::from("Only the owner or an administrator of all the tiers can create a tier family"));
+                        }
+                    }
+
+                    Ok(())
+                },
+                _ => Err(String::from("Cannot update or delete a tier family")),
+            }
+        },
+        links: [
+            from!(
+                "badge_class",
+                link_type: "badge_class->tier_family",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, .. } => {
+                            let tier_family: TierFamily = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            match tier_family.tiers.contains(link.link.base()) {
+                                true => Ok(()),
+                                false => Err(String::from("Cannot link \"badge_class->tier_family\" from a badge class that is not a tier of the family"))
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            )
+        ]
+    )
+}
+
+/** Handlers */
+
+pub fn create_tier_family(name: String, tiers: Vec<Address>) -> ZomeApiResult<Address> {
+    let tier_family = TierFamily {
+        name,
+        creator_address: AGENT_ADDRESS.clone(),
+        tiers: tiers.clone(),
+    };
+
+    let tier_family_entry = Entry::App("tier_family".into(), tier_family.into());
+    let tier_family_address = hdk::commit_entry(&tier_family_entry)?;
+
+    for tier in tiers {
+        hdk::link_entries(&tier, &tier_family_address, "badge_class->tier_family", "")?;
+    }
+
+    Ok(tier_family_address)
+}
+
+/**
+ * Returns the tier families that any version of the given badge class belongs to
+ */
+pub fn get_tier_families(badge_class: Address) -> ZomeApiResult<Vec<Address>> {
+    let mut tier_families: Vec<Address> = vec![];
+
+    for version in badge_class::get_class_versions(&badge_class)? {
+        let links = hdk::get_links(
+            &version,
+            LinkMatch::Exactly("badge_class->tier_family"),
+            LinkMatch::Any,
+        )?;
+
+        for address in links.addresses() {
+            if !tier_families.contains(&address) {
+                tier_families.push(address);
+            }
+        }
+    }
+
+    Ok(tier_families)
+}
+
+/**
+ * Returns the current tier of the agent, holding a badge of any version of each tier,
+ * and the requirements of the latest version of the next tier
+ */
+
+pub fn get_agent_tier(agent_address: Address, tier_family: Address) -> ZomeApiResult<AgentTier> {
+    let family: TierFamily = hdk::utils::get_as_type(tier_family.clone())?;
+
+    let mut current_tier: Option<usize> = None;
+    for (index, tier) in family.tiers.iter().enumerate() {
+        match badge::assert_holder_valid(tier, &agent_address) {
+            Ok(()) => current_tier = Some(index),
+            Err(_) => break,
+        }
+    }
+
+    let next_index = current_tier.map(|index| index + 1).unwrap_or(0);
+    let next_tier = match family.tiers.get(next_index) {
+        Some(next_tier) => {
+            let next_class_address = badge_class::get_latest_version(next_tier)?;
+            let next_class: BadgeClass = hdk::utils::get_as_type(next_class_address.clone())?;
+            let badge_address = Badge::initial(&agent_address, &next_class_address).address()?;
+
+            let issuers = match hdk::get_entry(&badge_address)? {
+                Some(_) => {
+                    let badge: Badge = hdk::utils::get_as_type(badge_address)?;
+                    badge.issuers
+                }
+                None => vec![],
+            };
+
+            Some(TierRequirements {
+                tier: next_index,
+                badge_class: next_class_address,
+                validators: next_class.validators,
+                min_lineages: next_class.min_lineages,
+                issuers,
+            })
+        }
+        None => None,
+    };
+
+    Ok(AgentTier {
+        tier_family,
+        current_tier,
+        current_badge_class: current_tier.map(|index| family.tiers[index].clone()),
+        next_tier,
+    })
+}
+
+/** Validation helpers */
+
+/**
+ * Badges of a tier can only be claimed for agents that hold the previous tier in every family the class belongs to,
+ * whichever versions of the tiers the family was created with
+ */
+pub fn assert_lower_tier_held(badge_class: &Address, recipient: &Address) -> ZomeApiResult<()> {
+    let versions = badge_class::get_class_versions(badge_class)?;
+
+    for tier_family_address in get_tier_families(badge_class.clone())? {
+        let family: TierFamily = hdk::utils::get_as_type(tier_family_address)?;
+
+        let position = family.tiers.iter().position(|tier| versions.contains(tier));
+
+        if let Some(index) = position {
+            if index > 0 && badge::assert_holder_valid(&family.tiers[index - 1], recipient).is_err()
+            {
+                return Err(ZomeApiError::from(format!(
+                    "Agent {} must hold tier {} of {} before being claimed for the next one",
+                    recipient,
+                    index - 1,
+                    family.name
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use chrono::DateTime;
+use hdk::holochain_core_types::chain_header::ChainHeader;
+use hdk::holochain_core_types::time::Iso8601;
+use hdk::holochain_wasm_utils::api_serialization::get_entry::{
+    GetEntryOptions, GetEntryResultType, StatusRequestKind,
+};
+use hdk::holochain_wasm_utils::api_serialization::{QueryArgsNames, QueryArgsOptions, QueryResult};
+use hdk::prelude::*;
+
+/**
+ * Converts the given timestamp to seconds since the epoch
+ */
+pub fn seconds(timestamp: &Iso8601) -> ZomeApiResult<i64> {
+    DateTime::parse_from_rfc3339(&timestamp.to_string())
+        .map(|date| date.timestamp())
+        .map_err(|_| ZomeApiError::from(format!("Could not parse timestamp {}", timestamp)))
+}
+
+/**
+ * Returns the time of the latest entry committed by the calling agent, which is the closest
+ * to the current time that a zome can read: checks made against it in handlers are only a lower bound,
+ * validation checks them again against the time of the commit
+ */
+pub fn latest_chain_time() -> ZomeApiResult<Iso8601> {
+    let result = hdk::query_result(
+        QueryArgsNames::QueryName("**".into()),
+        QueryArgsOptions {
+            headers: true,
+            ..Default::default()
+        },
+    )?;
+
+    let headers = match result {
+        QueryResult::Headers(headers) => headers,
+        _ => vec![],
+    };
+
+    latest_header_time(&headers)?.ok_or(ZomeApiError::from(String::from(
+        "The source chain of the agent is empty",
+    )))
+}
+
+/**
+ * Returns the time at which the latest version of the given entry was committed, if it exists
+ */
+pub fn latest_entry_time(address: &Address) -> ZomeApiResult<Option<Iso8601>> {
+    let result = hdk::get_entry_result(
+        address,
+        GetEntryOptions {
+            status_request: StatusRequestKind::All,
+            headers: true,
+            ..Default::default()
+        },
+    )?;
+
+    let history = match result.result {
+        GetEntryResultType::All(history) => history,
+        _ => return Ok(None),
+    };
+
+    let headers: Vec<ChainHeader> = history
+        .items
+        .into_iter()
+        .flat_map(|item| item.headers)
+        .collect();
+
+    latest_header_time(&headers)
+}
+
+fn latest_header_time(headers: &Vec<ChainHeader>) -> ZomeApiResult<Option<Iso8601>> {
+    let mut latest: Option<Iso8601> = None;
+    for header in headers {
+        let is_later = match &latest {
+            Some(time) => seconds(header.timestamp())? > seconds(time)?,
+            None => true,
+        };
+
+        if is_later {
+            latest = Some(header.timestamp().clone());
+        }
+    }
+
+    Ok(latest)
+}
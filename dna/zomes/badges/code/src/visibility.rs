@@ -0,0 +1,314 @@
+use crate::badge::Badge;
+use crate::badge_class;
+use crate::class_admins;
+use crate::organization::{self, OrganizationRole};
+use hdk::prelude::*;
+use hdk::AGENT_ADDRESS;
+
+/**
+ * Who the owner of a badge or a collection wants to show it to:
+ * - public: everyone
+ * - hidden: only the owner
+ * - shared: only the owner and the given agents
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum VisibilitySetting {
+    Public,
+    Hidden,
+    Shared { agents: Vec<Address> },
+}
+
+/**
+ * Visibility as it is published: instead of the agents it is shared with, a shared visibility only
+ * contains the address of a grant for each of them, which an agent can recompute to check that it was granted access.
+ *
+ * Visibility only filters the listings of this zome: badges, collections and their links stay public entries,
+ * readable by anyone who queries the DHT directly or already knows their addresses
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Visibility {
+    Public,
+    Hidden,
+    Shared { grants: Vec<Address> },
+}
+
+/**
+ * Access of an agent to a badge or a collection, only committed to compute its address.
+ *
+ * Grant addresses are plain hashes of the subject and the agent address, so they keep the list of agents
+ * out of the published visibility but do not make it secret: anyone can recompute the grant of a given agent
+ * and check whether a badge or a collection is shared with them
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct VisibilityGrant {
+    pub subject: Address,
+    pub agent_address: Address,
+}
+
+impl VisibilityGrant {
+    pub fn address(subject: &Address, agent_address: &Address) -> ZomeApiResult<Address> {
+        let grant = VisibilityGrant {
+            subject: subject.clone(),
+            agent_address: agent_address.clone(),
+        };
+
+        hdk::entry_address(&Entry::App("visibility_grant".into(), grant.into()))
+    }
+}
+
+impl Visibility {
+    /**
+     * Publishes the setting for the given badge or collection
+     */
+    pub fn from_setting(
+        subject: &Address,
+        setting: VisibilitySetting,
+    ) -> ZomeApiResult<Visibility> {
+        match setting {
+            VisibilitySetting::Public => Ok(Visibility::Public),
+            VisibilitySetting::Hidden => Ok(Visibility::Hidden),
+            VisibilitySetting::Shared { agents } => {
+                let grants = agents
+                    .iter()
+                    .map(|agent_address| VisibilityGrant::address(subject, agent_address))
+                    .collect::<ZomeApiResult<Vec<Address>>>()?;
+
+                Ok(Visibility::Shared { grants })
+            }
+        }
+    }
+
+    /**
+     * Returns whether the agent can see the given badge or collection of the owner
+     */
+    pub fn allows(
+        &self,
+        subject: &Address,
+        owner: &Address,
+        agent_address: &Address,
+    ) -> ZomeApiResult<bool> {
+        if owner == agent_address {
+            return Ok(true);
+        }
+
+        match self {
+            Visibility::Public => Ok(true),
+            Visibility::Hidden => Ok(false),
+            Visibility::Shared { grants } => {
+                Ok(grants.contains(&VisibilityGrant::address(subject, agent_address)?))
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct BadgeVisibility {
+    pub badge: Address,
+    pub recipient: Address,
+    pub visibility: Visibility,
+}
+
+impl BadgeVisibility {
+    /**
+     * Returns the initial visibility of the given badge, public for everyone
+     */
+    pub fn initial(badge: &Address, recipient: &Address) -> BadgeVisibility {
+        BadgeVisibility {
+            badge: badge.clone(),
+            recipient: recipient.clone(),
+            visibility: Visibility::Public,
+        }
+    }
+}
+
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "badge_visibility",
+        description: "The visibility of a badge chosen by its recipient",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: |validation_data: hdk::EntryValidationData<BadgeVisibility>| {
+            match validation_data {
+                EntryValidationData::Create { entry, validation_data } => {
+                    if !validation_data.sources().contains(&entry.recipient) {
+                        return Err(String::from("Only the recipient of a badge can set its visibility"));
+                    }
+
+                    let badge: Badge = hdk::utils::get_as_type(entry.badge.clone())?;
+
+                    if badge.recipient != entry.recipient {
+                        return Err(String::from("The recipient of the visibility must be the recipient of the badge"));
+                    }
+
+                    if badge.initial_address()? != entry.badge {
+                        return Err(String::from("The visibility must refer to the initial version of the badge"));
+                    }
+
+                    match entry.visibility {
+                        Visibility::Public => Ok(()),
+                        _ => Err(String::from("Badges must be public when their visibility is created")),
+                    }
+                },
+                EntryValidationData::Modify {
+                    new_entry,
+                    old_entry,
+                    validation_data,
+                    ..
+                } => {
+                    if new_entry.badge != old_entry.badge || new_entry.recipient != old_entry.recipient {
+                        return Err(String::from("Cannot change the badge or the recipient of a visibility"));
+                    }
+
+                    match validation_data.sources().contains(&old_entry.recipient) {
+                        true => Ok(()),
+                        false => Err(String::from("Only the recipient of a badge can change its visibility")),
+                    }
+                },
+                _ => Err(String::from("Cannot delete the visibility of a badge")),
+            }
+        },
+        links: []
+    )
+}
+
+/**
+ * Grants only exist as addresses in the published visibility of a badge or a collection,
+ * which agents recompute from this entry type to find out whether they were granted access
+ */
+pub fn grant_entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "visibility_grant",
+        description: "The access of an agent to a badge or a collection",
+        sharing: Sharing::Private,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: |_validation_data: hdk::EntryValidationData<VisibilityGrant>| {
+            Err(String::from("Visibility grants cannot be committed"))
+        },
+        links: []
+    )
+}
+
+/** Handlers */
+
+pub fn set_badge_visibility(
+    badge_address: Address,
+    setting: VisibilitySetting,
+) -> ZomeApiResult<Address> {
+    let badge: Badge = hdk::utils::get_as_type(badge_address)?;
+
+    if badge.recipient != AGENT_ADDRESS.clone() {
+        return Err(ZomeApiError::from(String::from(
+            "Only the recipient of a badge can set its visibility",
+        )));
+    }
+
+    let initial_badge_address = badge.initial_address()?;
+    let initial = BadgeVisibility::initial(&initial_badge_address, &badge.recipient);
+    let initial_entry = Entry::App("badge_visibility".into(), initial.clone().into());
+    let initial_address = hdk::entry_address(&initial_entry)?;
+
+    if let None = hdk::get_entry(&initial_address)? {
+        hdk::commit_entry(&initial_entry)?;
+    }
+
+    let new_visibility = BadgeVisibility {
+        visibility: Visibility::from_setting(&initial_badge_address, setting)?,
+        ..initial
+    };
+    let new_entry = Entry::App("badge_visibility".into(), new_visibility.into());
+    hdk::update_entry(new_entry, &initial_address)
+}
+
+/**
+ * Returns the current visibility of the given badge, or of the badge the given version belongs to
+ */
+pub fn get_badge_visibility(badge_address: Address) -> ZomeApiResult<Visibility> {
+    let badge: Badge = hdk::utils::get_as_type(badge_address)?;
+
+    get_visibility(&badge)
+}
+
+/** Helpers */
+
+/**
+ * Keeps only the badges that the calling agent is allowed to see, given any version of each badge.
+ * The issuers of a badge and the admins of its class see it whatever its visibility
+ */
+pub fn filter_visible_badges(badges: Vec<Address>) -> ZomeApiResult<Vec<Address>> {
+    let mut visible_badges: Vec<Address> = vec![];
+
+    for badge_address in badges {
+        let badge: Badge = hdk::utils::get_as_type(badge_address.clone())?;
+
+        if is_issuance_party(&badge)?
+            || get_visibility(&badge)?.allows(
+                &badge.initial_address()?,
+                &badge.recipient,
+                &AGENT_ADDRESS,
+            )?
+        {
+            visible_badges.push(badge_address);
+        }
+    }
+
+    Ok(visible_badges)
+}
+
+/**
+ * Returns the badges linked from the recipient with the given tag that the calling agent is allowed to see
+ */
+pub fn get_visible_badges_to_recipient(
+    recipient: &Address,
+    tag: &str,
+) -> ZomeApiResult<Vec<Address>> {
+    let links = hdk::get_links(
+        recipient,
+        LinkMatch::Exactly("recipient->badge"),
+        LinkMatch::Exactly(tag),
+    )?;
+
+    // The recipient always sees all their badges
+    if recipient == &AGENT_ADDRESS.clone() {
+        return Ok(links.addresses());
+    }
+
+    filter_visible_badges(links.addresses())
+}
+
+/**
+ * Returns whether the calling agent can act as one of the issuers of the badge, or administers its class
+ */
+fn is_issuance_party(badge: &Badge) -> ZomeApiResult<bool> {
+    for issuer in badge.issuers.iter() {
+        if organization::acts_for(issuer, &AGENT_ADDRESS, OrganizationRole::Issuer)? {
+            return Ok(true);
+        }
+    }
+
+    class_admins::is_class_admin(
+        &badge_class::get_latest_version(&badge.badge_class)?,
+        &AGENT_ADDRESS,
+    )
+}
+
+fn get_visibility(badge: &Badge) -> ZomeApiResult<Visibility> {
+    let initial = BadgeVisibility::initial(&badge.initial_address()?, &badge.recipient);
+    let initial_address = hdk::entry_address(&Entry::App(
+        "badge_visibility".into(),
+        initial.clone().into(),
+    ))?;
+
+    match hdk::get_entry(&initial_address)? {
+        Some(_) => {
+            let badge_visibility: BadgeVisibility = hdk::utils::get_as_type(initial_address)?;
+            Ok(badge_visibility.visibility)
+        }
+        None => Ok(initial.visibility),
+    }
+}
@@ -0,0 +1,46 @@
+use crate::profile;
+use anchors::{Anchor, AnchorValidator};
+use hdk::prelude::*;
+
+/** Entry type of the anchors that profiles are looked up by, by full handle or by handle prefix */
+pub const ANCHOR_ENTRY_TYPE: &str = "profile_anchor";
+
+/** Anchors claimed by exactly one profile each, with the full handle as text */
+pub const HANDLE_ANCHOR_TYPE: &str = "handle";
+/** Anchors linking to all the profiles whose handle starts with their text */
+pub const HANDLE_PREFIX_ANCHOR_TYPE: &str = "handle_prefix";
+
+/** Number of characters of the handle that make up the prefix anchors */
+pub const HANDLE_PREFIX_LENGTH: usize = 3;
+
+pub struct ProfileAnchors;
+
+impl AnchorValidator for ProfileAnchors {
+    const ENTRY_TYPE: &'static str = ANCHOR_ENTRY_TYPE;
+
+    fn validate_anchor(anchor: &Anchor) -> Result<(), String> {
+        let text = anchor.anchor_text.clone().unwrap_or_default();
+
+        let valid = match anchor.anchor_type.as_str() {
+            HANDLE_ANCHOR_TYPE => profile::assert_handle_valid(&text).is_ok(),
+            HANDLE_PREFIX_ANCHOR_TYPE => handle_prefix(&text) == text,
+            _ => false,
+        };
+
+        match valid {
+            true => Ok(()),
+            false => Err(format!(
+                "Invalid anchor {} of type {}",
+                text, anchor.anchor_type
+            )),
+        }
+    }
+}
+
+pub fn entry_def() -> ValidatingEntryType {
+    anchors::anchor_definition::<ProfileAnchors>()
+}
+
+pub fn handle_prefix(handle: &String) -> String {
+    handle.chars().take(HANDLE_PREFIX_LENGTH).collect()
+}
@@ -0,0 +1,85 @@
+#![feature(proc_macro_hygiene)]
+extern crate anchors;
+extern crate hdk;
+extern crate hdk_proc_macros;
+extern crate serde;
+extern crate serde_derive;
+extern crate serde_json;
+
+use hdk::holochain_persistence_api::cas::content::Address;
+use hdk::prelude::*;
+use hdk::{entry_definition::ValidatingEntryType, error::ZomeApiResult};
+use hdk_proc_macros::zome;
+
+// see https://developer.holochain.org/api/0.0.40-alpha1/hdk/ for info on using the hdk library
+
+pub mod anchor;
+pub mod profile;
+
+use profile::Profile;
+
+#[zome]
+mod profiles_zome {
+
+    #[init]
+    fn init() {
+        Ok(())
+    }
+
+    #[validate_agent]
+    pub fn validate_agent(validation_data: EntryValidationData<AgentId>) {
+        Ok(())
+    }
+
+    #[entry_def]
+    fn anchor_entry_def() -> ValidatingEntryType {
+        anchor::entry_def()
+    }
+
+    #[entry_def]
+    fn profile_entry_def() -> ValidatingEntryType {
+        profile::entry_def()
+    }
+
+    #[zome_fn("hc_public")]
+    fn create_profile(
+        handle: String,
+        display_name: String,
+        avatar: Option<String>,
+        bio: Option<String>,
+    ) -> ZomeApiResult<Address> {
+        profile::create_profile(handle, display_name, avatar, bio)
+    }
+
+    #[zome_fn("hc_public")]
+    fn update_profile(
+        display_name: String,
+        avatar: Option<String>,
+        bio: Option<String>,
+    ) -> ZomeApiResult<Address> {
+        profile::update_profile(display_name, avatar, bio)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_profile(agent_address: Address) -> ZomeApiResult<Option<Profile>> {
+        profile::get_profile(&agent_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_profiles(agent_addresses: Vec<Address>) -> ZomeApiResult<Vec<Option<Profile>>> {
+        agent_addresses
+            .iter()
+            .map(profile::get_profile)
+            .collect()
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_agent_by_handle(handle: String) -> ZomeApiResult<Option<Address>> {
+        profile::get_agent_by_handle(handle)
+    }
+
+    #[zome_fn("hc_public")]
+    fn search_profiles(handle_prefix: String) -> ZomeApiResult<Vec<Profile>> {
+        profile::search_profiles(handle_prefix)
+    }
+}
@@ -0,0 +1,326 @@
+use crate::anchor::{self, HANDLE_ANCHOR_TYPE, HANDLE_PREFIX_ANCHOR_TYPE, HANDLE_PREFIX_LENGTH};
+use hdk::prelude::*;
+use hdk::AGENT_ADDRESS;
+
+const MAX_HANDLE_LENGTH: usize = 30;
+
+/**
+ * Public profile of an agent, identified across the network by its unique handle
+ */
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct Profile {
+    pub agent_address: Address,
+    pub handle: String,
+    pub display_name: String,
+    pub avatar: Option<String>,
+    pub bio: Option<String>,
+}
+
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "profile",
+        description: "The public profile of an agent",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: |validation_data: hdk::EntryValidationData<Profile>| {
+            match validation_data {
+                EntryValidationData::Create { entry, validation_data } => {
+                    if !validation_data.sources().contains(&entry.agent_address) {
+                        return Err(String::from("Only the agent can create their own profile"));
+                    }
+
+                    assert_handle_valid(&entry.handle)?;
+                    assert_display_name_valid(&entry.display_name)?;
+
+                    Ok(())
+                },
+                EntryValidationData::Modify {
+                    new_entry,
+                    old_entry,
+                    validation_data,
+                    ..
+                } => {
+                    if !validation_data.sources().contains(&old_entry.agent_address) {
+                        return Err(String::from("Only the agent can update their own profile"));
+                    }
+
+                    if new_entry.agent_address != old_entry.agent_address || new_entry.handle != old_entry.handle {
+                        return Err(String::from("Cannot change the agent or the handle of a profile"));
+                    }
+
+                    assert_display_name_valid(&new_entry.display_name)?;
+
+                    Ok(())
+                },
+                _ => Err(String::from("Cannot delete a profile")),
+            }
+        },
+        links: [
+            from!(
+                "%agent_id",
+                link_type: "agent->profile",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, .. } => {
+                            let profile: Profile = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            if profile.agent_address != link.link.base().clone() {
+                                return Err(String::from("Cannot link \"agent->profile\" to the profile of another agent"));
+                            }
+
+                            match get_profile_address(link.link.base())? {
+                                Some(profile_address) if &profile_address != link.link.target() => {
+                                    Err(String::from("This agent already has a profile"))
+                                },
+                                _ => Ok(())
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            ),
+            from!(
+                anchor::ANCHOR_ENTRY_TYPE,
+                link_type: "handle->profile",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, validation_data } => {
+                            let handle = anchors::get_anchor_of_type(link.link.base(), HANDLE_ANCHOR_TYPE)?;
+                            let profile: Profile = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            if handle.anchor_text != Some(profile.handle.clone()) {
+                                return Err(String::from("Cannot link \"handle->profile\" to a profile with another handle"));
+                            }
+
+                            if !validation_data.sources().contains(&profile.agent_address) {
+                                return Err(String::from("Only the agent can claim the handle of their profile"));
+                            }
+
+                            match get_handle_owners(link.link.base())?.iter().any(|owner| owner != &profile.agent_address) {
+                                true => Err(format!("Handle {} is already taken", profile.handle)),
+                                false => Ok(())
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            ),
+            from!(
+                anchor::ANCHOR_ENTRY_TYPE,
+                link_type: "handle_prefix->profile",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, .. } => {
+                            let prefix = anchors::get_anchor_of_type(link.link.base(), HANDLE_PREFIX_ANCHOR_TYPE)?;
+                            let profile: Profile = hdk::utils::get_as_type(link.link.target().clone())?;
+
+                            match prefix.anchor_text == Some(anchor::handle_prefix(&profile.handle)) {
+                                true => Ok(()),
+                                false => Err(String::from("Cannot link \"handle_prefix->profile\" to a profile with another handle prefix"))
+                            }
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
+            )
+        ]
+    )
+}
+
+/** Handlers */
+
+pub fn create_profile(
+    handle: String,
+    display_name: String,
+    avatar: Option<String>,
+    bio: Option<String>,
+) -> ZomeApiResult<Address> {
+    if let Some(_) = get_profile_address(&AGENT_ADDRESS)? {
+        return Err(ZomeApiError::from(String::from(
+            "This agent already has a profile",
+        )));
+    }
+
+    let handle = handle.to_lowercase();
+    if let Some(_) = get_agent_by_handle(handle.clone())? {
+        return Err(ZomeApiError::from(format!(
+            "Handle {} is already taken",
+            handle
+        )));
+    }
+
+    let profile = Profile {
+        agent_address: AGENT_ADDRESS.clone(),
+        handle: handle.clone(),
+        display_name,
+        avatar,
+        bio,
+    };
+
+    let profile_entry = Entry::App("profile".into(), profile.into());
+    let profile_address = hdk::commit_entry(&profile_entry)?;
+
+    hdk::link_entries(&AGENT_ADDRESS, &profile_address, "agent->profile", "")?;
+    hdk::link_entries(
+        &anchors::anchor::<anchor::ProfileAnchors>(HANDLE_ANCHOR_TYPE, &handle)?,
+        &profile_address,
+        "handle->profile",
+        "",
+    )?;
+    hdk::link_entries(
+        &anchors::anchor::<anchor::ProfileAnchors>(
+            HANDLE_PREFIX_ANCHOR_TYPE,
+            &anchor::handle_prefix(&handle),
+        )?,
+        &profile_address,
+        "handle_prefix->profile",
+        "",
+    )?;
+
+    Ok(profile_address)
+}
+
+/**
+ * Updates the profile of the calling agent, keeping its handle
+ */
+pub fn update_profile(
+    display_name: String,
+    avatar: Option<String>,
+    bio: Option<String>,
+) -> ZomeApiResult<Address> {
+    let profile_address = match get_profile_address(&AGENT_ADDRESS)? {
+        Some(address) => address,
+        None => {
+            return Err(ZomeApiError::from(String::from(
+                "This agent does not have a profile yet",
+            )))
+        }
+    };
+
+    let mut profile: Profile = hdk::utils::get_as_type(profile_address.clone())?;
+    profile.display_name = display_name;
+    profile.avatar = avatar;
+    profile.bio = bio;
+
+    let profile_entry = Entry::App("profile".into(), profile.into());
+    hdk::update_entry(profile_entry, &profile_address)
+}
+
+pub fn get_profile(agent_address: &Address) -> ZomeApiResult<Option<Profile>> {
+    match get_profile_address(agent_address)? {
+        Some(profile_address) => hdk::utils::get_as_type(profile_address).map(Some),
+        None => Ok(None),
+    }
+}
+
+pub fn get_agent_by_handle(handle: String) -> ZomeApiResult<Option<Address>> {
+    let handle_address = anchors::anchor_address::<anchor::ProfileAnchors>(
+        HANDLE_ANCHOR_TYPE,
+        &handle.to_lowercase(),
+    )?;
+
+    Ok(get_handle_owners(&handle_address)?.first().cloned())
+}
+
+/**
+ * Returns the profiles whose handle starts with the given prefix, sorted by handle
+ */
+pub fn search_profiles(handle_prefix: String) -> ZomeApiResult<Vec<Profile>> {
+    let handle_prefix = handle_prefix.to_lowercase();
+
+    if handle_prefix.chars().count() < HANDLE_PREFIX_LENGTH {
+        return Err(ZomeApiError::from(format!(
+            "Handle prefix must have at least {} characters",
+            HANDLE_PREFIX_LENGTH
+        )));
+    }
+
+    let prefix_address = anchors::anchor_address::<anchor::ProfileAnchors>(
+        HANDLE_PREFIX_ANCHOR_TYPE,
+        &anchor::handle_prefix(&handle_prefix),
+    )?;
+
+    let mut profiles: Vec<Profile> = hdk::utils::get_links_and_load_type(
+        &prefix_address,
+        LinkMatch::Exactly("handle_prefix->profile"),
+        LinkMatch::Any,
+    )?;
+
+    profiles.retain(|profile| profile.handle.starts_with(&handle_prefix));
+    profiles.sort_by(|a, b| a.handle.cmp(&b.handle));
+    profiles.dedup_by(|a, b| a.agent_address == b.agent_address);
+
+    Ok(profiles)
+}
+
+/** Helpers */
+
+fn get_profile_address(agent_address: &Address) -> ZomeApiResult<Option<Address>> {
+    let links = hdk::get_links(
+        agent_address,
+        LinkMatch::Exactly("agent->profile"),
+        LinkMatch::Any,
+    )?;
+
+    Ok(links.addresses().first().cloned())
+}
+
+/**
+ * Returns the agents whose profiles are linked from the given handle anchor
+ */
+fn get_handle_owners(handle_address: &Address) -> ZomeApiResult<Vec<Address>> {
+    let profiles: Vec<Profile> = hdk::utils::get_links_and_load_type(
+        handle_address,
+        LinkMatch::Exactly("handle->profile"),
+        LinkMatch::Any,
+    )?;
+
+    let mut owners: Vec<Address> = vec![];
+    for profile in profiles {
+        if !owners.contains(&profile.agent_address) {
+            owners.push(profile.agent_address);
+        }
+    }
+
+    Ok(owners)
+}
+
+/** Validation helpers */
+
+/**
+ * Handles are between 3 and 30 lowercase letters, digits or underscores
+ */
+pub fn assert_handle_valid(handle: &String) -> ZomeApiResult<()> {
+    let length = handle.chars().count();
+    let valid_chars = handle
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+
+    match length >= HANDLE_PREFIX_LENGTH && length <= MAX_HANDLE_LENGTH && valid_chars {
+        true => Ok(()),
+        false => Err(ZomeApiError::from(format!(
+            "Handle {} must have between {} and {} lowercase letters, digits or underscores",
+            handle, HANDLE_PREFIX_LENGTH, MAX_HANDLE_LENGTH
+        ))),
+    }
+}
+
+fn assert_display_name_valid(display_name: &String) -> ZomeApiResult<()> {
+    match display_name.trim().is_empty() {
+        true => Err(ZomeApiError::from(String::from(
+            "Display name cannot be empty",
+        ))),
+        false => Ok(()),
+    }
+}
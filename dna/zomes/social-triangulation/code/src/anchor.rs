@@ -0,0 +1,29 @@
+use anchors::{Anchor, AnchorValidator};
+use hdk::prelude::*;
+
+/** Entry type of the single anchor from which every vouched agent is linked */
+pub const ANCHOR_ENTRY_TYPE: &str = "social_anchor";
+
+pub const MEMBERS_ANCHOR_TYPE: &str = "members";
+/** Text of the members anchor linking to all vouches */
+pub const VOUCHED_ANCHOR_TEXT: &str = "vouched";
+
+/**
+ * Only allows the anchor listing the vouched agents
+ */
+pub struct SocialAnchors;
+
+impl AnchorValidator for SocialAnchors {
+    const ENTRY_TYPE: &'static str = ANCHOR_ENTRY_TYPE;
+
+    fn validate_anchor(anchor: &Anchor) -> Result<(), String> {
+        match anchor == &Anchor::new(MEMBERS_ANCHOR_TYPE, VOUCHED_ANCHOR_TEXT) {
+            true => Ok(()),
+            false => Err(format!("Invalid anchor of type {}", anchor.anchor_type)),
+        }
+    }
+}
+
+pub fn entry_def() -> ValidatingEntryType {
+    anchors::anchor_definition::<SocialAnchors>()
+}
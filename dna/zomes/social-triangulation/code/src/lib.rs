@@ -1,4 +1,5 @@
 #![feature(proc_macro_hygiene)]
+extern crate anchors;
 extern crate hdk;
 extern crate hdk_proc_macros;
 extern crate serde;
@@ -12,6 +13,7 @@ use hdk_proc_macros::zome;
 
 // see https://developer.holochain.org/api/0.0.40-alpha1/hdk/ for info on using the hdk library
 
+pub mod anchor;
 pub mod members;
 pub mod vouch;
 
@@ -41,6 +43,11 @@ mod social_triangulation_zome {
         }
     }
 
+    #[entry_def]
+    fn anchor_entry_def() -> ValidatingEntryType {
+        anchor::entry_def()
+    }
+
     #[entry_def]
     fn vouch_entry_def() -> ValidatingEntryType {
         vouch::entry_def()
@@ -50,4 +57,9 @@ mod social_triangulation_zome {
     fn vouch_for_agent(agent_address: Address) -> ZomeApiResult<()> {
         vouch::vouch_for_agent(&agent_address)
     }
+
+    #[zome_fn("hc_public")]
+    fn get_vouched_agents() -> ZomeApiResult<Vec<Address>> {
+        vouch::get_vouched_agents()
+    }
 }
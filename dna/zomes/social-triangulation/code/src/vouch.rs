@@ -1,3 +1,4 @@
+use crate::anchor;
 use hdk::prelude::*;
 
 pub fn entry_def() -> ValidatingEntryType {
@@ -30,6 +31,22 @@ pub fn entry_def() -> ValidatingEntryType {
                         _ => Err(String::from("Cannot delete links"))
                     }
                 }
+            ),
+            from!(
+                anchor::ANCHOR_ENTRY_TYPE,
+                link_type: "members->vouch",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, .. } => {
+                            anchors::get_anchor_of_type(link.link.base(), anchor::MEMBERS_ANCHOR_TYPE)?;
+                            Ok(())
+                        },
+                        _ => Err(String::from("Cannot delete links"))
+                    }
+                }
             )
         ]
     )
@@ -55,5 +72,40 @@ pub fn vouch_for_agent(agent_address: &Address) -> ZomeApiResult<()> {
         &hdk::AGENT_ADDRESS.to_string(),
     )?;
 
+    hdk::link_entries(
+        &anchors::anchor::<anchor::SocialAnchors>(
+            anchor::MEMBERS_ANCHOR_TYPE,
+            anchor::VOUCHED_ANCHOR_TEXT,
+        )?,
+        &vouch_address,
+        "members->vouch",
+        "",
+    )?;
+
     Ok(())
 }
+
+/**
+ * Returns all the agents that have been vouched for by at least one member
+ */
+pub fn get_vouched_agents() -> ZomeApiResult<Vec<Address>> {
+    let links = hdk::get_links(
+        &anchors::anchor_address::<anchor::SocialAnchors>(
+            anchor::MEMBERS_ANCHOR_TYPE,
+            anchor::VOUCHED_ANCHOR_TEXT,
+        )?,
+        LinkMatch::Exactly("members->vouch"),
+        LinkMatch::Any,
+    )?;
+
+    let mut agents: Vec<Address> = vec![];
+    for vouch_address in links.addresses() {
+        let agent_address: Address = hdk::utils::get_as_type(vouch_address)?;
+
+        if !agents.contains(&agent_address) {
+            agents.push(agent_address);
+        }
+    }
+
+    Ok(agents)
+}
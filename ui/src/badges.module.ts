@@ -31,7 +31,10 @@ export class BadgesModule extends MicroModule {
   async onLoad(container: interfaces.Container) {
     const badgeProvider = createHolochainProvider(this.instance, 'badges');
 
+    const profileProvider = createHolochainProvider(this.instance, 'profiles');
+
     container.bind(BadgeTypes.BadgeProvider).to(badgeProvider);
+    container.bind(BadgeTypes.ProfileProvider).to(profileProvider);
   }
 
   submodules = [
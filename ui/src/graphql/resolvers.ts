@@ -2,20 +2,107 @@ import { HolochainProvider } from '@uprtcl/connections';
 
 import { BadgeTypes } from '../types';
 
+/**
+ * Returns the profile of the given agent, or null if they have not created one
+ */
+function getProfile(container, agentAddress: string) {
+  const profileProvider: HolochainProvider = container.get(
+    BadgeTypes.ProfileProvider
+  );
+
+  return profileProvider.call('get_profile', {
+    agent_address: agentAddress
+  });
+}
+
+/**
+ * Returns the entry of the given badge class
+ */
+async function getBadgeClass(container, badgeClassAddress: string) {
+  const badgeProvider: HolochainProvider = container.get(
+    BadgeTypes.BadgeProvider
+  );
+
+  const entry = await badgeProvider.call('get_entry', {
+    address: badgeClassAddress
+  });
+
+  return JSON.parse(entry.App[1]);
+}
+
+/**
+ * Returns the given badge with its attestations, loading them if only its address is known
+ */
+async function getAttestedBadge(container, badge) {
+  if (typeof badge !== 'string') return badge;
+
+  const badgeProvider: HolochainProvider = container.get(
+    BadgeTypes.BadgeProvider
+  );
+
+  const entry = await badgeProvider.call('get_entry', {
+    address: badge
+  });
+  const { recipient, badge_class } = JSON.parse(entry.App[1]);
+
+  return badgeProvider.call('get_badge', {
+    badge_class,
+    recipient
+  });
+}
+
 export const resolvers = {
   BadgeClass: {
     id(parent) {
       return parent;
     },
+    async issuancePolicy(parent, _, { container }) {
+      const badgeClass = await getBadgeClass(container, parent);
+
+      return badgeClass.issuance_policy;
+    },
+    async minLineages(parent, _, { container }) {
+      const badgeClass = await getBadgeClass(container, parent);
+
+      return badgeClass.min_lineages;
+    },
+    async maxHolders(parent, _, { container }) {
+      const badgeClass = await getBadgeClass(container, parent);
+
+      return badgeClass.max_holders;
+    },
+    async issuanceOpensAt(parent, _, { container }) {
+      const badgeClass = await getBadgeClass(container, parent);
+
+      return badgeClass.issuance_opens_at;
+    },
+    async issuanceClosesAt(parent, _, { container }) {
+      const badgeClass = await getBadgeClass(container, parent);
+
+      return badgeClass.issuance_closes_at;
+    },
 
     async allBadges(parent, _, { container }) {
       const badgeProvider: HolochainProvider = container.get(
         BadgeTypes.BadgeProvider
       );
 
-      return badgeProvider.call('get_badges_for_class', {
+      const classBadges = await badgeProvider.call('get_badges_for_class', {
+        badge_class: parent
+      });
+
+      return classBadges.badges;
+    },
+    async remainingCapacity(parent, _, { container }) {
+      const badgeProvider: HolochainProvider = container.get(
+        BadgeTypes.BadgeProvider
+      );
+
+      const classBadges = await badgeProvider.call('get_badges_for_class', {
         badge_class: parent
       });
+
+      return classBadges.remaining_capacity;
     },
     async badge(parent, { agentAddress }, { container }) {
       const badgeProvider: HolochainProvider = container.get(
@@ -26,6 +113,31 @@ export const resolvers = {
         badge_class: parent,
         recipient: agentAddress
       });
+    },
+    async issuanceReport(parent, _, { container }) {
+      const badgeProvider: HolochainProvider = container.get(
+        BadgeTypes.BadgeProvider
+      );
+
+      return badgeProvider.call('get_issuance_report', {
+        badge_class: parent
+      });
+    }
+  },
+  IssuanceReport: {
+    issuanceRings(parent) {
+      return parent.issuance_rings;
+    },
+    sharedUpstream(parent) {
+      return parent.shared_upstream;
+    },
+    issuanceBursts(parent) {
+      return parent.issuance_bursts;
+    }
+  },
+  SharedUpstream: {
+    upstreamIssuers(parent) {
+      return parent.upstream_issuers;
     }
   },
   Query: {
@@ -50,21 +162,41 @@ export const resolvers = {
         BadgeTypes.BadgeProvider
       );
 
-      return badgeProvider.call('create_badge_class', input);
+      const {
+        minLineages,
+        issuancePolicy,
+        maxHolders,
+        issuanceOpensAt,
+        issuanceClosesAt,
+        ...badgeClass
+      } = input;
+
+      return badgeProvider.call('create_badge_class', {
+        ...badgeClass,
+        min_lineages: minLineages,
+        issuance_policy: issuancePolicy,
+        max_holders: maxHolders,
+        issuance_opens_at: issuanceOpensAt,
+        issuance_closes_at: issuanceClosesAt
+      });
     },
     async claimAgentDeservesBadge(
       _,
-      { recipientAgent, badgeClassId, evidences },
+      { recipientAgent, badgeClassId, evidences, statement, confidence },
       { container }
     ) {
       const badgeProvider: HolochainProvider = container.get(
         BadgeTypes.BadgeProvider
       );
 
-      return badgeProvider.call('claim_user_deser', {
+      return badgeProvider.call('claim_agent_deserves_badge', {
         recipient: recipientAgent,
         badge_class: badgeClassId,
-        evidences: evidences
+        evidences: evidences,
+        statement,
+        confidence,
+        criteria: [],
+        on_behalf_of: null
       });
     },
     async receiveOwnBadge(_, { badgeClassId }, { container }) {
@@ -77,10 +209,45 @@ export const resolvers = {
       });
     }
   },
+  Badge: {
+    id(parent) {
+      return typeof parent === 'string' ? parent : parent.address;
+    },
+    async attestations(parent, _, { container }) {
+      const attestedBadge = await getAttestedBadge(container, parent);
+
+      return attestedBadge.attestations;
+    }
+  },
+  Attestation: {
+    id(parent) {
+      return parent.address;
+    },
+    issuer(parent) {
+      return parent.attestation.issuer;
+    },
+    statement(parent) {
+      return parent.attestation.statement;
+    },
+    confidence(parent) {
+      return parent.attestation.confidence;
+    },
+    issuedAt(parent) {
+      return parent.issued_at;
+    }
+  },
+  Profile: {
+    displayName(parent) {
+      return parent.display_name;
+    }
+  },
   Agent: {
     id(parent) {
       return parent;
     },
+    profile(parent, _, { container }) {
+      return getProfile(container, parent);
+    },
     async issuedBadges(parent, _, { container }) {
       const badgeProvider: HolochainProvider = container.get(
         BadgeTypes.BadgeProvider
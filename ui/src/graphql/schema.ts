@@ -3,12 +3,20 @@ import { gql } from 'apollo-boost';
 export const badgesTypeDefs = gql`
   type Agent {
     id: ID!
-    
+    profile: Profile
+
     issuedBadges: [Badge!]!
     receivedBadges: [Badge!]!
     createdBadgeClasses: [BadgeClass!]!
   }
 
+  type Profile {
+    handle: String!
+    displayName: String!
+    avatar: String
+    bio: String
+  }
+
   type BadgeClass implements Entity {
     id: ID!
 
@@ -17,9 +25,35 @@ export const badgesTypeDefs = gql`
     creator: Agent!
     image: string!
     validators: Int!
+    minLineages: Int
+    issuancePolicy: String!
+    maxHolders: Int
+    issuanceOpensAt: String
+    issuanceClosesAt: String
 
     allBadges: [Badge!]!
+    remainingCapacity: Int
     badge(agentAddress: ID!): Badge!
+    issuanceReport: IssuanceReport!
+  }
+
+  type IssuanceReport {
+    issuanceRings: [[Agent!]!]!
+    sharedUpstream: [SharedUpstream!]!
+    issuanceBursts: [IssuanceBurst!]!
+  }
+
+  type SharedUpstream {
+    agent: Agent!
+    issuers: [Agent!]!
+    upstreamIssuers: [Agent!]!
+  }
+
+  type IssuanceBurst {
+    issuer: Agent!
+    recipients: [Agent!]!
+    from: String!
+    to: String!
   }
 
   type Badge implements Entity {
@@ -28,10 +62,20 @@ export const badgesTypeDefs = gql`
     recipient: [Agent!]!
     evidences: [Entity!]!
     issuers: [Agent!]!
+    attestations: [Attestation!]!
 
     class: BadgeClass!
   }
 
+  type Attestation {
+    id: ID!
+
+    issuer: Agent!
+    statement: String!
+    confidence: String!
+    issuedAt: String
+  }
+
   extend type Query {
     allBadgeClasses: [BadgeClass!]!
     me: Agent!
@@ -43,6 +87,11 @@ export const badgesTypeDefs = gql`
     description: String!
     image: String!
     validators: Int!
+    minLineages: Int
+    issuancePolicy: String
+    maxHolders: Int
+    issuanceOpensAt: String
+    issuanceClosesAt: String
   }
 
   extend type Mutation {
@@ -51,6 +100,8 @@ export const badgesTypeDefs = gql`
       recipientAgent: ID!
       badgeClassId: ID!
       evidences: [ID!]!
+      statement: String!
+      confidence: String!
     ): Badge!
     receiveOwnBadge(badgeClassId: ID!): Badge!
   }
@@ -1,5 +1,6 @@
 export const BadgeTypes = {
-  BadgeProvider: Symbol('badge-provider')
+  BadgeProvider: Symbol('badge-provider'),
+  ProfileProvider: Symbol('profile-provider')
 };
 
 export interface Badge {